@@ -0,0 +1,30 @@
+use macroquad::prelude::*;
+
+use crate::hole;
+use crate::sprite::Sprite;
+
+pub struct Assets {
+    pub background: Texture2D,
+    pub mole: Sprite,
+    pub helmet_mole: Sprite,
+    pub cat: Sprite,
+    pub hole: Texture2D,
+    pub mound: Texture2D,
+}
+
+impl Assets {
+    pub async fn load() -> Self {
+        let background_image = load_image("images/background1.png")
+            .await
+            .expect("Failed to load background1 image");
+
+        Self {
+            background: Texture2D::from_image(&background_image),
+            mole: Sprite::load("images/mole.png").await,
+            helmet_mole: Sprite::load("images/helmet_mole.png").await,
+            cat: Sprite::load("images/cat.png").await,
+            hole: Texture2D::from_image(&hole::hole_image()),
+            mound: Texture2D::from_image(&hole::mound_image()),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::game2::{Game, MoleKind, Seat};
+use crate::keyboard;
+use crate::settings::Settings;
+
+const MAX_ENERGY: f32 = 10.0;
+const START_ENERGY: f32 = 5.0;
+const ENERGY_PER_SECOND: f32 = 1.5;
+// Moles the hammer misses score 1 for the mole player, a whacked cat scores this much
+const CAT_HIT_POINTS: u32 = 5;
+
+// What player two can send up: kind, energy cost, keys that pick it
+const KINDS: [(MoleKind, f32, [KeyCode; 2]); 3] = [
+    (MoleKind::Normal, 2.0, [KeyCode::Key1, KeyCode::Kp1]),
+    (MoleKind::Helmet, 5.0, [KeyCode::Key2, KeyCode::Kp2]),
+    (MoleKind::Cat, 3.0, [KeyCode::Key3, KeyCode::Kp3]),
+];
+
+// Asymmetric versus on the Medium board: player one whacks with the mouse while player
+// two picks which hole pops and what comes out of it, paying for each from an energy bar
+// that refills over time.
+pub struct Asymmetric {
+    game: Game,
+    bindings: Bindings,
+    hole_keys: Vec<Input>,
+    selected: usize, // Index into KINDS
+    energy: f32,
+}
+
+impl Asymmetric {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let game = Game::new(2, assets, settings, bindings)
+            .with_seat(Seat::Mouse)
+            .with_manual_spawns();
+        Self {
+            game,
+            bindings: bindings.clone(),
+            hole_keys: keyboard::hole_inputs(2, 2, bindings),
+            selected: 0,
+            energy: START_ENERGY,
+        }
+    }
+
+    // Points for the mole player: escaped moles plus whacked cats
+    fn mole_score(&self) -> u32 {
+        self.game.escaped() + self.game.cats_hit() * CAT_HIT_POINTS
+    }
+
+    // Returns true when the players want to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.game.update();
+
+        if self.game.is_over() || self.game.is_paused() {
+            return back_to_menu;
+        }
+
+        self.energy = (self.energy + ENERGY_PER_SECOND * get_frame_time()).min(MAX_ENERGY);
+        if let Some(kind) = KINDS.iter().position(|(_, _, keys)| keys.iter().any(|&key| is_key_pressed(key))) {
+            self.selected = kind;
+        }
+        if let Some(index) = keyboard::pressed_hole(&self.hole_keys) {
+            let (kind, cost, _) = KINDS[self.selected];
+            if self.energy >= cost && self.game.pop(index, kind) {
+                self.energy -= cost;
+            }
+        }
+
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        self.game.draw();
+        if self.game.is_paused() {
+            return;
+        }
+        let labels: Vec<String> = self.hole_keys.iter().map(|&input| keyboard::key_label(input)).collect();
+        self.game.draw_hole_labels(&labels);
+
+        // The mole player's panel in the top right corner
+        let left = screen_width() - 380.0;
+        draw_rectangle(left - 20.0, 10.0, 390.0, 200.0, Color::new(0.0, 0.0, 0.0, 0.5));
+        draw_text(&format!("Moles: {}", self.mole_score()), left, 45.0, 40.0, WHITE);
+        draw_rectangle(left, 60.0, 340.0, 20.0, DARKGRAY);
+        draw_rectangle(left, 60.0, 340.0 * self.energy / MAX_ENERGY, 20.0, SKYBLUE);
+        draw_text(&format!("Energy {:.0}/{:.0}", self.energy.floor(), MAX_ENERGY), left, 100.0, 22.0, WHITE);
+        for (i, (kind, cost, _)) in KINDS.iter().enumerate() {
+            let color = if i == self.selected { GOLD } else if self.energy >= *cost { WHITE } else { GRAY };
+            draw_text(&format!("{}: {:?} ({:.0})", i + 1, kind, cost), left, 130.0 + i as f32 * 25.0, 24.0, color);
+        }
+
+        if self.game.is_over() {
+            let (hammer, moles) = (self.game.score(), self.mole_score() as i32);
+            let result = match hammer.cmp(&moles) {
+                std::cmp::Ordering::Greater => "The hammer wins!",
+                std::cmp::Ordering::Less => "The moles win!",
+                std::cmp::Ordering::Equal => "Draw!",
+            };
+            let size = measure_text(result, None, 60, 1.0);
+            draw_text(result, (screen_width() - size.width) / 2.0, screen_height() / 2.0, 60.0, GOLD);
+            let hint = format!("Press {} to restart or {} to return to menu.", self.bindings.label(Action::Restart), self.bindings.label(Action::Back));
+            let size = measure_text(&hint, None, 30, 1.0);
+            draw_text(&hint, (screen_width() - size.width) / 2.0, screen_height() / 2.0 + 40.0, 30.0, WHITE);
+        }
+    }
+}
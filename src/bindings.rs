@@ -0,0 +1,235 @@
+use macroquad::prelude::*;
+use std::fs;
+
+const BINDINGS_PATH: &str = "bindings.txt";
+
+// Something the player can do, independent of which key or button does it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Hit,
+    Pet,
+    Pause,
+    Back,
+    Quit,
+    Restart,
+    Hole2x2(usize), // Hole keys for the 2x2 grid, row by row
+    Hole3x3(usize), // Hole keys for grids up to 3x3, row by row
+}
+
+// A key or a mouse button an action is bound to
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Input {
+    pub fn is_pressed(self) -> bool {
+        match self {
+            Input::Key(key) => is_key_pressed(key),
+            Input::Mouse(button) => is_mouse_button_pressed(button),
+        }
+    }
+
+    // A letter key, which typing mode needs for the words
+    pub fn is_letter(self) -> bool {
+        matches!(self, Input::Key(key) if BINDABLE_KEYS[..26].contains(&key))
+    }
+
+    // Short name shown in the HUD and saved in the bindings file
+    pub fn name(self) -> String {
+        match self {
+            Input::Key(key) => format!("{:?}", key),
+            Input::Mouse(MouseButton::Left) => "MouseLeft".to_string(),
+            Input::Mouse(MouseButton::Right) => "MouseRight".to_string(),
+            Input::Mouse(MouseButton::Middle) => "MouseMiddle".to_string(),
+            Input::Mouse(_) => "Mouse".to_string(),
+        }
+    }
+
+    fn parse(name: &str) -> Option<Input> {
+        match name {
+            "MouseLeft" => Some(Input::Mouse(MouseButton::Left)),
+            "MouseRight" => Some(Input::Mouse(MouseButton::Right)),
+            "MouseMiddle" => Some(Input::Mouse(MouseButton::Middle)),
+            _ => BINDABLE_KEYS
+                .iter()
+                .find(|key| format!("{:?}", key) == name)
+                .map(|&key| Input::Key(key)),
+        }
+    }
+}
+
+// Keys that can be saved to and read back from the bindings file
+pub const BINDABLE_KEYS: [KeyCode; 76] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpAdd, KeyCode::KpSubtract, KeyCode::KpMultiply, KeyCode::KpDivide, KeyCode::KpEnter,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+];
+
+// Every action in the order shown on the settings screen, with its default input
+fn defaults() -> Vec<(Action, Input)> {
+    let mut defaults = vec![
+        (Action::Hit, Input::Mouse(MouseButton::Left)),
+        (Action::Pet, Input::Mouse(MouseButton::Right)),
+        (Action::Pause, Input::Key(KeyCode::Escape)),
+        (Action::Back, Input::Key(KeyCode::Backspace)),
+        (Action::Quit, Input::Key(KeyCode::F10)),
+        (Action::Restart, Input::Key(KeyCode::R)),
+    ];
+    // The 2x2 grid follows the numpad, bigger grids the left-hand letter block
+    let numpad = [KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp4, KeyCode::Kp5];
+    for (i, key) in numpad.into_iter().enumerate() {
+        defaults.push((Action::Hole2x2(i), Input::Key(key)));
+    }
+    let letters = [
+        KeyCode::Q, KeyCode::W, KeyCode::E,
+        KeyCode::A, KeyCode::S, KeyCode::D,
+        KeyCode::Z, KeyCode::X, KeyCode::C,
+    ];
+    for (i, key) in letters.into_iter().enumerate() {
+        defaults.push((Action::Hole3x3(i), Input::Key(key)));
+    }
+    defaults
+}
+
+// Which input triggers each action, read from `bindings.txt` as `action = input` lines
+#[derive(Clone)]
+pub struct Bindings {
+    bindings: Vec<(Action, Input)>,
+}
+
+impl Bindings {
+    pub fn load() -> Self {
+        let mut bindings = Bindings { bindings: defaults() };
+        if let Ok(text) = fs::read_to_string(BINDINGS_PATH) {
+            for line in text.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if let Some((name, input)) = line.split_once('=') {
+                    let action = bindings.bindings.iter().map(|&(a, _)| a).find(|a| a.file_name() == name.trim());
+                    if let (Some(action), Some(input)) = (action, Input::parse(input.trim())) {
+                        bindings.set(action, input);
+                    }
+                }
+            }
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        let text: String = self
+            .bindings
+            .iter()
+            .map(|(action, input)| format!("{} = {}\n", action.file_name(), input.name()))
+            .collect();
+        if let Err(err) = fs::write(BINDINGS_PATH, text) {
+            eprintln!("Failed to save {}: {}", BINDINGS_PATH, err);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.bindings = defaults();
+    }
+
+    pub fn actions(&self) -> &[(Action, Input)] {
+        &self.bindings
+    }
+
+    pub fn get(&self, action: Action) -> Input {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|&(_, input)| input)
+            .expect("every action has a binding")
+    }
+
+    pub fn set(&mut self, action: Action, input: Input) {
+        if let Some(binding) = self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            binding.1 = input;
+        }
+    }
+
+    // Another action that `input` already triggers and that can be in use alongside `action`
+    pub fn conflict(&self, action: Action, input: Input) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|&&(other, bound)| bound == input && other != action && !other.can_share_input(action))
+            .map(|&(other, _)| other)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.get(action).is_pressed()
+    }
+
+    // Name of the input bound to an action, for on-screen hints
+    pub fn label(&self, action: Action) -> String {
+        self.get(action).name()
+    }
+}
+
+impl Action {
+    // The 2x2 and 3x3 hole keys are never in use at the same time
+    fn can_share_input(self, other: Action) -> bool {
+        matches!(
+            (self, other),
+            (Action::Hole2x2(_), Action::Hole3x3(_)) | (Action::Hole3x3(_), Action::Hole2x2(_))
+        )
+    }
+
+    fn file_name(self) -> String {
+        match self {
+            Action::Hit => "hit".to_string(),
+            Action::Pet => "pet".to_string(),
+            Action::Pause => "pause".to_string(),
+            Action::Back => "back".to_string(),
+            Action::Quit => "quit".to_string(),
+            Action::Restart => "restart".to_string(),
+            Action::Hole2x2(i) => format!("hole2x2_{}", i + 1),
+            Action::Hole3x3(i) => format!("hole3x3_{}", i + 1),
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Action::Hit => "Hit".to_string(),
+            Action::Pet => "Pet a cat".to_string(),
+            Action::Pause => "Pause".to_string(),
+            Action::Back => "Back to menu".to_string(),
+            Action::Quit => "Quit".to_string(),
+            Action::Restart => "Restart round".to_string(),
+            Action::Hole2x2(i) => format!("2x2 hole {}", i + 1),
+            Action::Hole3x3(i) => format!("3x3 hole {}", i + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_in_use_at_the_same_time_conflict() {
+        let bindings = Bindings { bindings: defaults() };
+        let r = Input::Key(KeyCode::R);
+        assert_eq!(bindings.conflict(Action::Hole3x3(0), r), Some(Action::Restart));
+        assert_eq!(bindings.conflict(Action::Restart, r), None);
+        assert_eq!(bindings.conflict(Action::Hole3x3(2), Input::Key(KeyCode::Q)), Some(Action::Hole3x3(0)));
+        // Only one grid's hole keys are used at a time
+        assert_eq!(bindings.conflict(Action::Hole2x2(0), Input::Key(KeyCode::Q)), None);
+        assert_eq!(bindings.conflict(Action::Hole2x2(0), Input::Key(KeyCode::Kp8)), Some(Action::Hole2x2(1)));
+    }
+}
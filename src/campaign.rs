@@ -0,0 +1,227 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::{Game, MoleKind};
+use crate::gamepad::{self, Button};
+use crate::highscores::{HighScores, Run};
+use crate::menu::{draw_button, pick_option};
+use crate::settings::Settings;
+
+const ALL_KINDS: &[MoleKind] = &[MoleKind::Normal, MoleKind::Helmet, MoleKind::Cat];
+
+struct Level {
+    name: &'static str,
+    grid: (usize, usize), // Columns and rows of holes
+    seconds: f64,
+    kinds: &'static [MoleKind], // What may come up, in the usual proportions
+    target: i32,                // Score needed to pass and unlock the next level
+}
+
+const LEVELS: [Level; 8] = [
+    Level { name: "First dig", grid: (2, 2), seconds: 45.0, kinds: &[MoleKind::Normal], target: 12 },
+    Level { name: "Hard hats", grid: (2, 2), seconds: 60.0, kinds: &[MoleKind::Normal, MoleKind::Helmet], target: 18 },
+    Level { name: "Mind the cat", grid: (2, 2), seconds: 60.0, kinds: &[MoleKind::Normal, MoleKind::Cat], target: 18 },
+    Level { name: "Full house", grid: (2, 2), seconds: 60.0, kinds: ALL_KINDS, target: 22 },
+    Level { name: "Wide field", grid: (3, 2), seconds: 60.0, kinds: &[MoleKind::Normal, MoleKind::Helmet], target: 28 },
+    Level { name: "Cat country", grid: (3, 2), seconds: 60.0, kinds: ALL_KINDS, target: 28 },
+    Level { name: "Nine holes", grid: (3, 3), seconds: 60.0, kinds: ALL_KINDS, target: 35 },
+    Level { name: "The big dig", grid: (3, 3), seconds: 90.0, kinds: ALL_KINDS, target: 55 },
+];
+
+// Chance out of 1.0 that a new arrival is a normal mole, and that it is a helmet mole,
+// keeping the usual 70/15/15 split between the kinds the level allows
+fn mix(kinds: &[MoleKind]) -> (f64, f64) {
+    let weight = |kind: MoleKind| match kind {
+        _ if !kinds.contains(&kind) => 0.0,
+        MoleKind::Normal => 0.7,
+        MoleKind::Helmet | MoleKind::Cat => 0.15,
+    };
+    let total = weight(MoleKind::Normal) + weight(MoleKind::Helmet) + weight(MoleKind::Cat);
+    (weight(MoleKind::Normal) / total, weight(MoleKind::Helmet) / total)
+}
+
+fn mode(level: usize) -> String {
+    format!("campaign-{}", level + 1)
+}
+
+// Campaign: Medium rules over a run of levels, each with its own layout, length, moles and
+// score to beat. Best scores go to `highscores.txt`, and a level is unlocked once the
+// one before it has been passed.
+pub struct Campaign {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    highscores: HighScores,
+    playing: Option<(usize, Game)>, // Level index and its round, None on the level select
+    recorded: Option<Run>,          // This round, once it's over and saved
+}
+
+impl Campaign {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            highscores: HighScores::load(),
+            playing: None,
+            recorded: None,
+        }
+    }
+
+    fn best_score(&self, level: usize) -> Option<i32> {
+        self.highscores.best_scores(&mode(level)).first().map(|run| run.score)
+    }
+
+    fn is_passed(&self, level: usize) -> bool {
+        self.best_score(level).is_some_and(|score| score >= LEVELS[level].target)
+    }
+
+    fn is_unlocked(&self, level: usize) -> bool {
+        level == 0 || self.is_passed(level - 1)
+    }
+
+    fn start(&mut self, level: usize) {
+        let spec = &LEVELS[level];
+        let (normal, helmet) = mix(spec.kinds);
+        let game = Game::new(2, self.assets.clone(), &self.settings, &self.bindings)
+            .with_grid(spec.grid.0, spec.grid.1)
+            .with_mix(normal, helmet)
+            .with_duration(spec.seconds);
+        self.playing = Some((level, game));
+        self.recorded = None;
+    }
+
+    fn level_button(level: usize) -> Rect {
+        let (col, row) = (level % 4, level / 4);
+        Rect::new(screen_width() / 2.0 - 470.0 + col as f32 * 240.0, 230.0 + row as f32 * 150.0, 220.0, 80.0)
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        let Some((level, game)) = &mut self.playing else {
+            let buttons: Vec<Rect> = (0..LEVELS.len()).map(Self::level_button).collect();
+            if let Some(level) = pick_option(&buttons).filter(|&level| self.is_unlocked(level)) {
+                self.start(level);
+            }
+            return back;
+        };
+
+        let level = *level;
+        // Back leaves the level for the level select, not the campaign
+        if game.update() {
+            self.playing = None;
+            return false;
+        }
+        if game.is_over() && self.recorded.is_none() {
+            let run = Run { score: game.score(), seconds: game.elapsed() };
+            self.highscores.record(&mode(level), run);
+            self.highscores.save();
+            self.recorded = Some(run);
+        }
+
+        if self.bindings.pressed(Action::Restart) {
+            self.start(level);
+        } else if let Some(run) = self.recorded {
+            let next = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A);
+            if next {
+                let passed = run.score >= LEVELS[level].target;
+                if passed && level + 1 < LEVELS.len() {
+                    self.start(level + 1);
+                } else {
+                    self.playing = None;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn draw(&self) {
+        let Some((level, game)) = &self.playing else {
+            self.draw_level_select();
+            return;
+        };
+
+        game.draw();
+        let spec = &LEVELS[*level];
+        let banner = format!("Level {}: {}   Goal: {}", level + 1, spec.name, spec.target);
+        let width = measure_text(&banner, None, 32, 1.0).width;
+        draw_text(&banner, screen_width() - width - 20.0, 40.0, 32.0, WHITE);
+
+        let Some(this_run) = self.recorded else {
+            return;
+        };
+        draw_rectangle(140.0, 100.0, 900.0, 260.0, Color::new(0.0, 0.0, 0.0, 0.7));
+        let passed = this_run.score >= spec.target;
+        if passed {
+            draw_text(&format!("Level passed with {} points!", this_run.score), 180.0, 170.0, 50.0, GOLD);
+        } else {
+            draw_text(&format!("{} points, {} needed", this_run.score, spec.target), 180.0, 170.0, 50.0, RED);
+        }
+        let next = match LEVELS.get(level + 1) {
+            Some(next) if passed => format!("Enter or A for level {}: {}", level + 2, next.name),
+            None if passed => "Campaign complete! Enter or A for the level select".to_string(),
+            _ => "Enter or A for the level select".to_string(),
+        };
+        draw_text(&next, 180.0, 240.0, 32.0, WHITE);
+        draw_text(
+            &format!(
+                "{} retries the level, {} goes back to the level select.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+            ),
+            180.0,
+            320.0,
+            26.0,
+            LIGHTGRAY,
+        );
+    }
+
+    fn draw_level_select(&self) {
+        draw_text("CAMPAIGN", 60.0, 100.0, 60.0, BLACK);
+        draw_text("Pass a level to unlock the next. Click one or press its number to play.", 60.0, 150.0, 28.0, DARKGRAY);
+
+        for (i, level) in LEVELS.iter().enumerate() {
+            let rect = Self::level_button(i);
+            let (label, color) = if !self.is_unlocked(i) {
+                ("Locked".to_string(), DARKGRAY)
+            } else if self.is_passed(i) {
+                (format!("{}. {}", i + 1, level.name), DARKGREEN)
+            } else {
+                (format!("{}. {}", i + 1, level.name), ORANGE)
+            };
+            draw_button(rect.x, rect.y, rect.w, rect.h, &label, color);
+
+            let best = match self.best_score(i) {
+                Some(score) => format!("Best {} / {}", score, level.target),
+                None => format!("Goal {}", level.target),
+            };
+            draw_text(&best, rect.x + 10.0, rect.y + rect.h + 28.0, 24.0, DARKGRAY);
+        }
+
+        draw_text(
+            &format!("Press {} to return to menu.", self.bindings.label(Action::Back)),
+            60.0,
+            screen_height() - 40.0,
+            24.0,
+            DARKGRAY,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_keep_the_usual_proportions_of_their_kinds() {
+        assert_eq!(mix(ALL_KINDS), (0.7, 0.15));
+        assert_eq!(mix(&[MoleKind::Normal]), (1.0, 0.0));
+        let (normal, helmet) = mix(&[MoleKind::Normal, MoleKind::Cat]);
+        assert!((normal - 0.7 / 0.85).abs() < 1e-9);
+        assert_eq!(helmet, 0.0);
+    }
+}
@@ -0,0 +1,58 @@
+use macroquad::prelude::*;
+
+// A round's clock: seconds played, stopped while paused and once the round is over
+pub struct Clock {
+    start_time: f64,
+    paused_at: Option<f64>,
+    ended_at: Option<f64>,
+}
+
+impl Clock {
+    pub fn start() -> Self {
+        Self { start_time: get_time(), paused_at: None, ended_at: None }
+    }
+
+    // Seconds the round has been running, not counting pauses
+    pub fn elapsed(&self) -> f64 {
+        self.paused_at.or(self.ended_at).unwrap_or_else(get_time) - self.start_time
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.ended_at.is_some()
+    }
+
+    // Neither paused nor over
+    pub fn is_running(&self) -> bool {
+        !self.is_paused() && !self.is_over()
+    }
+
+    pub fn end(&mut self) {
+        if self.ended_at.is_none() {
+            self.ended_at = Some(get_time());
+        }
+    }
+
+    // Pause or resume. Resuming returns how long the pause lasted, for pushing the
+    // round's other timers forward so nothing jumps. Once the round is over there is
+    // nothing left to pause.
+    pub fn toggle_pause(&mut self) -> Option<f64> {
+        if self.is_over() {
+            return None;
+        }
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                let paused_for = get_time() - paused_at;
+                self.start_time += paused_for;
+                Some(paused_for)
+            }
+            None => {
+                self.paused_at = Some(get_time());
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use macroquad::prelude::*;
+
+use crate::bindings::{Action, Bindings};
+use crate::gamepad::{self, Button};
+use crate::menu::{draw_button, is_button_clicked};
+
+// Draw the controls screen. Returns true when the player wants to go back to the menu.
+pub fn draw_controls(bindings: &Bindings) -> bool {
+    draw_text("CONTROLS", 60.0, 80.0, 60.0, BLACK);
+
+    let lines = [
+        "Mouse / keyboard (change these in Settings)".to_string(),
+        format!(
+            "  {}: whack a mole     {}: pet a cat",
+            bindings.label(Action::Hit),
+            bindings.label(Action::Pet),
+        ),
+        format!(
+            "  {}: pause     {}: restart     {}: back to menu     {}: quit",
+            bindings.label(Action::Pause),
+            bindings.label(Action::Restart),
+            bindings.label(Action::Back),
+            bindings.label(Action::Quit),
+        ),
+        "  Keyboard mode: hit each hole with the key shown under it, Shift + key pets a cat".to_string(),
+        "Gamepad (Xbox layout, Linux only)".to_string(),
+        "  Menu: D-pad or left stick to choose, A to select".to_string(),
+        "  In game: left stick or D-pad moves the hammer".to_string(),
+        "  A: whack     X: pet a cat     Start: pause     Back: back to menu".to_string(),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        let heading = !line.starts_with(' ');
+        draw_text(
+            line,
+            60.0,
+            140.0 + i as f32 * 32.0,
+            if heading { 32.0 } else { 26.0 },
+            if heading { BLACK } else { DARKGRAY },
+        );
+    }
+
+    // Gamepads are picked up as they are plugged in
+    let names = gamepad::connected_names();
+    let status = if !cfg!(target_os = "linux") {
+        "Gamepads are only supported on Linux".to_string()
+    } else if names.is_empty() {
+        "No gamepad connected".to_string()
+    } else {
+        format!("Gamepads connected: {}", names.join(", "))
+    };
+    draw_text(&status, 60.0, 480.0, 28.0, if names.is_empty() { GRAY } else { DARKGREEN });
+
+    let (button_x, button_y, button_width, button_height) = (60.0, 520.0, 200.0, 60.0);
+    draw_button(button_x, button_y, button_width, button_height, "Back", GRAY);
+
+    let clicked = is_mouse_button_pressed(MouseButton::Left) && {
+        let (mouse_x, mouse_y) = mouse_position();
+        is_button_clicked(button_x, button_y, button_width, button_height, mouse_x, mouse_y)
+    };
+    clicked || bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::B) || gamepad::is_button_pressed(Button::A)
+}
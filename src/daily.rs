@@ -0,0 +1,333 @@
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::Game;
+use crate::gamepad::{self, Button};
+use crate::highscores::{HighScores, Run};
+use crate::settings::Settings;
+
+// The same frenzy for everyone, whatever their own `frenzy_seconds`
+const FRENZY_SECONDS: f32 = 10.0;
+
+// A calendar day
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    // Today in the local time zone where we can read it, in UTC otherwise
+    pub fn today() -> Date {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() as i64)
+            .unwrap_or(0);
+        local_date(now).unwrap_or_else(|| Date::from_days(now.div_euclid(86_400)))
+    }
+
+    // The date `days` after 1970-01-01, see Howard Hinnant's `civil_from_days`
+    fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153; // Counting from March
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date { year: year as i32, month: month as u32, day: day as u32 }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn local_date(now: i64) -> Option<Date> {
+    let time = now as libc::time_t;
+    // SAFETY: `tm` is plain old data, and both pointers are only used during the call
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(Date { year: tm.tm_year + 1900, month: (tm.tm_mon + 1) as u32, day: tm.tm_mday as u32 })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn local_date(_now: i64) -> Option<Date> {
+    None
+}
+
+// A twist on the Medium rules for the day
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mutator {
+    QuickMoles,
+    HelmetRush,
+    CatCafe,
+    Sprint,
+    Marathon,
+}
+
+impl Mutator {
+    fn description(self) -> &'static str {
+        match self {
+            Mutator::QuickMoles => "Quick moles: holes fill half again as often",
+            Mutator::HelmetRush => "Helmet rush: 40% of moles wear a helmet",
+            Mutator::CatCafe => "Cat cafe: 40% of arrivals are cats",
+            Mutator::Sprint => "Sprint: the round only lasts 30 seconds",
+            Mutator::Marathon => "Marathon: the round lasts 90 seconds",
+        }
+    }
+
+    fn apply(self, game: Game) -> Game {
+        match self {
+            Mutator::QuickMoles => game.with_spawn_rate(1.5),
+            Mutator::HelmetRush => game.with_mix(0.45, 0.4),
+            Mutator::CatCafe => game.with_mix(0.5, 0.1),
+            Mutator::Sprint => game.with_duration(30.0),
+            Mutator::Marathon => game.with_duration(90.0),
+        }
+    }
+}
+
+// Everything that makes one day's challenge: the same on every machine for that date
+#[derive(PartialEq, Debug)]
+pub struct Challenge {
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+}
+
+impl Challenge {
+    pub fn for_date(date: Date) -> Challenge {
+        let number = date.year as u64 * 10_000 + date.month as u64 * 100 + date.day as u64;
+        let seed = number.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+
+        // At most one mutator for each of speed, mole mix and round length
+        let mut mutators = Vec::new();
+        if rng.gen_range(0, 2) == 1 {
+            mutators.push(Mutator::QuickMoles);
+        }
+        match rng.gen_range(0, 3) {
+            1 => mutators.push(Mutator::HelmetRush),
+            2 => mutators.push(Mutator::CatCafe),
+            _ => {}
+        }
+        match rng.gen_range(0, 3) {
+            1 => mutators.push(Mutator::Sprint),
+            2 => mutators.push(Mutator::Marathon),
+            _ => {}
+        }
+        Challenge { seed, mutators }
+    }
+}
+
+// Daily challenge: everyone gets the same moles and mutators on the same date. The first
+// run of the day is the official one, started or not finished it still counts; every run
+// after that is practice, kept in its own table.
+pub struct Daily {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    date: Date,
+    challenge: Challenge,
+    highscores: HighScores,
+    game: Option<Game>, // None on the intro screen
+    official: bool,     // Whether the current run is the official attempt
+    recorded: Option<Run>,
+}
+
+impl Daily {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let date = Date::today();
+        Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            date,
+            challenge: Challenge::for_date(date),
+            highscores: HighScores::load(),
+            game: None,
+            official: false,
+            recorded: None,
+        }
+    }
+
+    fn official_mode(&self) -> String {
+        format!("daily-{}", self.date)
+    }
+
+    fn practice_mode(&self) -> String {
+        format!("daily-{}-practice", self.date)
+    }
+
+    // Today's official run, once it has been played
+    fn official_run(&self) -> Option<Run> {
+        self.highscores.best_scores(&self.official_mode()).first().copied()
+    }
+
+    fn start(&mut self) {
+        let mut game = Game::with_seed(2, self.assets.clone(), &self.settings, &self.bindings, self.challenge.seed)
+            .with_frenzy(FRENZY_SECONDS);
+        for &mutator in &self.challenge.mutators {
+            game = mutator.apply(game);
+        }
+        self.game = Some(game);
+        self.official = self.official_run().is_none();
+        self.recorded = None;
+        // Save a score of 0 straight away, in case the game is closed before the run ends
+        if self.official {
+            self.highscores.replace(&self.official_mode(), Run { score: 0, seconds: 0.0 });
+            self.highscores.save();
+        }
+    }
+
+    // Save the current run, even if it was cut short
+    fn record(&mut self) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        if self.recorded.is_some() {
+            return;
+        }
+        let run = Run { score: game.score(), seconds: game.elapsed() };
+        if self.official {
+            self.highscores.replace(&self.official_mode(), run);
+        } else {
+            self.highscores.record(&self.practice_mode(), run);
+        }
+        self.highscores.save();
+        self.recorded = Some(run);
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let Some(game) = &mut self.game else {
+            let start = is_key_pressed(KeyCode::Enter)
+                || is_key_pressed(KeyCode::KpEnter)
+                || is_mouse_button_pressed(MouseButton::Left)
+                || gamepad::is_button_pressed(Button::A);
+            if start {
+                self.start();
+            }
+            return self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+        };
+
+        let back_to_menu = game.update();
+        let over = game.is_over();
+        // Leaving or restarting the official run early still uses it up
+        if over || back_to_menu || (self.official && self.bindings.pressed(Action::Restart)) {
+            self.record();
+        }
+        if self.bindings.pressed(Action::Restart) {
+            self.start();
+        }
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        let Some(game) = &self.game else {
+            self.draw_intro();
+            return;
+        };
+
+        game.draw();
+        let kind = if self.official { "Official attempt" } else { "Practice" };
+        let banner = format!("Daily {} - {}", self.date, kind);
+        let width = measure_text(&banner, None, 32, 1.0).width;
+        draw_text(&banner, screen_width() - width - 20.0, 40.0, 32.0, if self.official { GOLD } else { WHITE });
+
+        let Some(this_run) = self.recorded else {
+            return;
+        };
+        draw_rectangle(140.0, 100.0, 900.0, 300.0, Color::new(0.0, 0.0, 0.0, 0.7));
+        let result = if self.official {
+            format!("Official score for {}: {}", self.date, this_run.score)
+        } else {
+            format!("Practice score: {}", this_run.score)
+        };
+        draw_text(&result, 180.0, 170.0, 50.0, GOLD);
+        if let Some(official) = self.official_run().filter(|_| !self.official) {
+            draw_text(&format!("Today's official score: {}", official.score), 180.0, 230.0, 32.0, WHITE);
+        }
+        if let Some(best) = self.highscores.best_scores(&self.practice_mode()).first() {
+            draw_text(&format!("Best practice score today: {}", best.score), 180.0, 270.0, 32.0, WHITE);
+        }
+        draw_text(
+            &format!(
+                "Press {} to practice again or {} to return to menu.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+            ),
+            180.0,
+            370.0,
+            26.0,
+            LIGHTGRAY,
+        );
+    }
+
+    fn draw_intro(&self) {
+        draw_text("DAILY CHALLENGE", 60.0, 100.0, 60.0, BLACK);
+        draw_text(&format!("{} - the same moles for everyone today", self.date), 60.0, 150.0, 32.0, DARKGRAY);
+
+        let mut y = 210.0;
+        if self.challenge.mutators.is_empty() {
+            draw_text("No mutators today, plain Medium rules.", 60.0, y, 28.0, DARKGRAY);
+            y += 40.0;
+        }
+        for mutator in &self.challenge.mutators {
+            draw_text(mutator.description(), 60.0, y, 28.0, DARKBLUE);
+            y += 40.0;
+        }
+
+        y += 20.0;
+        match self.official_run() {
+            Some(official) => {
+                draw_text(&format!("Official score: {}. Further runs are practice.", official.score), 60.0, y, 28.0, BLACK);
+            }
+            None => {
+                draw_text("Your official attempt is next. Leaving it early still uses it up.", 60.0, y, 28.0, BLACK);
+            }
+        }
+        draw_text(
+            &format!("Press Enter, click or A to start, {} to return to menu.", self.bindings.label(Action::Back)),
+            60.0,
+            y + 50.0,
+            28.0,
+            DARKGRAY,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_turn_into_calendar_dates() {
+        assert_eq!(Date::from_days(0), Date { year: 1970, month: 1, day: 1 });
+        assert_eq!(Date::from_days(11_016), Date { year: 2000, month: 2, day: 29 });
+        assert_eq!(Date::from_days(20_745), Date { year: 2026, month: 10, day: 19 });
+        assert_eq!(Date::from_days(-1), Date { year: 1969, month: 12, day: 31 });
+    }
+
+    #[test]
+    fn every_player_gets_the_same_challenge_on_a_date() {
+        let today = Date { year: 2026, month: 10, day: 19 };
+        let tomorrow = Date { year: 2026, month: 10, day: 20 };
+        assert_eq!(Challenge::for_date(today), Challenge::for_date(today));
+        assert_ne!(Challenge::for_date(today).seed, Challenge::for_date(tomorrow).seed);
+    }
+}
@@ -1,20 +1,32 @@
 use macroquad::prelude::*;
 use std::sync::Arc;
 
-const MOLE_WIDTH: f32 = 256.0;
-const MOLE_HEIGHT: f32 = 256.0;
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::clock::Clock;
+use crate::gamepad::{self, Button};
+use crate::hole;
+use crate::hud::{self, Notices, Popups};
+use crate::input::{self, PressKind};
+use crate::keyboard;
+use crate::layout;
+use crate::settings::Settings;
+use crate::sprite::Sprite;
+
 
 pub struct Game {
     moles: Vec<Mole>,
     score: i32,
-    message: String,
-    message_timer: f64,
-    background_texture: Arc<Texture2D>,
-    mole_texture: Arc<Texture2D>,
-    difficulty: u8,
-    start_time: f64,
+    popups: Popups,
+    notices: Notices,
+    assets: Arc<Assets>,
+    hit_leniency: f32,
+    scale: f32,
+    bindings: Bindings,
+    hole_keys: Vec<Input>,   // Empty unless keyboard mode is on
+    cursor: Vec2,            // Gamepad hammer position
+    clock: Clock,
     game_duration: f64,
-    game_over: bool,
 }
 
 pub struct Mole {
@@ -44,118 +56,201 @@ impl Mole {
         }
     }
 
-    pub fn draw(&self, mole_texture: &Arc<Texture2D>) {
-        if self.visible {
-            // Draw mole image, centered
-            draw_texture(
-                mole_texture,
-                self.x - MOLE_WIDTH / 2.0,
-                self.y - MOLE_HEIGHT / 2.0,
-                WHITE,
-            );
-        }
+    // How far the mole is out of its hole, animating the last appear/disappear
+    fn rise(&self) -> f32 {
+        hole::rise(self.visible, get_time() - self.last_toggle)
+    }
+
+    pub fn draw(&self, assets: &Assets, scale: f32) {
+        // Draw the hole with the mole rising out of it
+        hole::draw(assets, &assets.mole, vec2(self.x, self.y), scale, self.rise());
+    }
+
+    // Draw `text` on the mole, rising and sinking with it
+    pub fn draw_badge(&self, assets: &Assets, scale: f32, text: &str) {
+        hole::draw_badge(&assets.mole, vec2(self.x, self.y), scale, self.rise(), text, 0);
     }
 
-    pub fn is_clicked(&self, mouse_x: f32, mouse_y: f32) -> bool {
+    pub fn is_clicked(&self, mouse_x: f32, mouse_y: f32, mole_sprite: &Sprite, scale: f32, leniency: f32) -> bool {
         if !self.visible {
             return false;
         }
-        // Rectangle collision detection
-        let left = self.x - MOLE_WIDTH / 2.0;
-        let right = self.x + MOLE_WIDTH / 2.0;
-        let top = self.y - MOLE_HEIGHT / 2.0;
-        let bottom = self.y + MOLE_HEIGHT / 2.0;
-        
-        mouse_x >= left && mouse_x <= right && mouse_y >= top && mouse_y <= bottom
+        // Only the opaque part of the mole above ground counts
+        hole::contains(mole_sprite, vec2(self.x, self.y), scale, self.rise(), vec2(mouse_x, mouse_y), leniency)
+    }
+
+    // Hit test the hole itself, or the mole when one is up, so empty holes can be picked too
+    pub fn is_hole_clicked(&self, mouse_x: f32, mouse_y: f32, mole_sprite: &Sprite, scale: f32, leniency: f32) -> bool {
+        self.is_clicked(mouse_x, mouse_y, mole_sprite, scale, leniency)
+            || hole::ring_contains(mole_sprite, vec2(self.x, self.y), scale, vec2(mouse_x, mouse_y), leniency)
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.last_toggle = get_time();
     }
 
     pub fn hide(&mut self) {
         self.visible = false;
         self.last_toggle = get_time();
     }
+
+    // Fully out of the hole, not rising or sinking
+    pub fn is_up(&self) -> bool {
+        self.visible && self.rise() >= 1.0
+    }
+
+    pub fn position(&self) -> Vec2 {
+        vec2(self.x, self.y)
+    }
+
+    // Push the mole's timer forward, e.g. after the game was paused
+    pub fn delay(&mut self, seconds: f64) {
+        self.last_toggle += seconds;
+    }
 }
 
 impl Game {
     pub fn new(
         difficulty: u8,
-        background_texture: Arc<Texture2D>,
-        mole_texture: Arc<Texture2D>,
+        assets: Arc<Assets>,
+        settings: &Settings,
+        bindings: &Bindings,
     ) -> Self {
-        // Positions of four moles
-        let positions = vec![
-            (450.0, 200.0),  // Top left
-            (850.0, 200.0),  // Top right
-            (450.0, 500.0),  // Bottom left
-            (850.0, 500.0),  // Bottom right
-        ];
+        // Positions of four moles in a 2x2 grid
+        let scale = settings.easy_scale;
+        let positions = layout::grid(2, 2, scale);
 
         let moles = positions
             .iter()
             .map(|&(x, y)| Mole::new(x, y))
             .collect();
 
+        let mut notices = Notices::new();
+        notices.push(&format!("Difficulty: {} - Click the moles!", difficulty), DARKGREEN);
+
         Self {
             moles,
             score: 0,
-            message: format!("Difficulty: {} - Click the moles!", difficulty),
-            message_timer: 0.0,
-            background_texture,
-            mole_texture,
-            difficulty,
-            start_time: get_time(),
+            popups: Popups::new(),
+            notices,
+            assets,
+            hit_leniency: settings.hit_leniency,
+            scale,
+            bindings: bindings.clone(),
+            hole_keys: if settings.keyboard_mode { keyboard::hole_inputs(2, 2, bindings) } else { Vec::new() },
+            cursor: vec2(screen_width() / 2.0, screen_height() / 2.0),
+            clock: Clock::start(),
             game_duration: 60.0,
-            game_over: false,
         }
     }
 
     pub fn update(&mut self) -> bool {
+        let back_to_menu = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        // Pause or resume, the round clock doesn't run while paused
+        if self.bindings.pressed(Action::Pause) || gamepad::is_button_pressed(Button::Start) {
+            self.toggle_pause();
+        }
+        if self.clock.is_paused() {
+            return back_to_menu;
+        }
+
         // Update all moles
         for mole in &mut self.moles {
-            if self.game_over {
+            if self.clock.is_over() {
                 mole.visible = false;
             } else {
                 mole.update();
             }
         }
 
-        // Handle mouse clicks
-        if is_mouse_button_pressed(MouseButton::Left) {
-            let (mouse_x, mouse_y) = mouse_position();
-            let mut hit = false;
-
-            for mole in &mut self.moles {
-                if mole.is_clicked(mouse_x, mouse_y) {
-                    self.score += 1;
-                    mole.hide();
-                    self.message = format!("Hit! Score: {}", self.score);
-                    self.message_timer = get_time();
-                    hit = true;
-                    break;
-                }
+        // Handle every click and touch since last frame, in order
+        for press in input::presses() {
+            if press.kind == PressKind::Primary {
+                self.whack(press.x, press.y);
             }
+        }
 
-            if !hit {
-                self.message = "Missed!".to_string();
-                self.message_timer = get_time();
-            }
+        // Handle hole keys in keyboard mode
+        if let Some(index) = keyboard::pressed_hole(&self.hole_keys) {
+            let (x, y) = (self.moles[index].x, self.moles[index].y);
+            self.whack_hole(index, x, y);
         }
         
+        // Handle the gamepad hammer
+        if gamepad::connected_count() > 0 {
+            self.cursor = hud::move_cursor(self.cursor, gamepad::left_stick());
+            if gamepad::is_button_pressed(Button::A) {
+                self.whack(self.cursor.x, self.cursor.y);
+            }
+        }
+
+        self.popups.update();
+        self.notices.update();
+
         // Check for game over
-        if get_time() - self.start_time >= self.game_duration { 
-            self.game_over = true;
+        if self.elapsed() >= self.game_duration { 
+            self.clock.end();
         }
 
         // Check for return to menu
-        is_key_pressed(KeyCode::Q)
+        back_to_menu
+    }
+
+    // Seconds the round has been running, not counting pauses
+    fn elapsed(&self) -> f64 {
+        self.clock.elapsed()
+    }
+
+    fn toggle_pause(&mut self) {
+        // Push every timer forward by the pause so nothing jumps on resume
+        if let Some(paused_for) = self.clock.toggle_pause() {
+            for mole in &mut self.moles {
+                mole.delay(paused_for);
+            }
+        }
+    }
+
+    // Whack whatever is under the point (mouse or gamepad hammer)
+    fn whack(&mut self, x: f32, y: f32) {
+        let clicked = self
+            .moles
+            .iter()
+            .position(|mole| mole.is_clicked(x, y, &self.assets.mole, self.scale, self.hit_leniency));
+
+        match clicked {
+            Some(index) => self.whack_hole(index, x, y),
+            None => self.popups.spawn("Miss", x, y, LIGHTGRAY),
+        }
+    }
+
+    // Whack the mole in hole `index`, showing the result at (x, y)
+    fn whack_hole(&mut self, index: usize, x: f32, y: f32) {
+        let mole = &mut self.moles[index];
+        if mole.visible {
+            self.score += 1;
+            mole.hide();
+            self.popups.spawn("+1", x, y, GREEN);
+        } else {
+            self.popups.spawn("Miss", x, y, LIGHTGRAY);
+        }
     }
 
     pub fn draw(&self) {
         // Draw background
-        draw_texture(&self.background_texture, 0.0, 0.0, WHITE);
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
 
-        // Draw all moles
-        for mole in &self.moles {
-            mole.draw(&self.mole_texture);
+        // Draw all holes from the back row forward so lower holes overlap upper ones
+        let mut moles: Vec<&Mole> = self.moles.iter().collect();
+        moles.sort_by(|a, b| a.y.total_cmp(&b.y));
+        for mole in moles {
+            mole.draw(&self.assets, self.scale);
+        }
+
+        // Draw each hole's key in keyboard mode
+        for (mole, &input) in self.moles.iter().zip(&self.hole_keys) {
+            hole::draw_label(&self.assets.mole, vec2(mole.x, mole.y), self.scale, &keyboard::key_label(input));
         }
 
         // Draw UI
@@ -166,9 +261,9 @@ impl Game {
             40.0,
             WHITE,
         );
-        if !self.game_over {
+        if !self.clock.is_over() {
             draw_text(
-                &format!("Time: {:.0}", self.game_duration - (get_time() - self.start_time)),
+                &format!("Time: {:.0}", self.game_duration - self.elapsed()),
                 20.0,
                 80.0,
                 40.0,
@@ -183,20 +278,32 @@ impl Game {
                 WHITE,
             );
         }
-        // Draw message (disappears after 0.25 seconds)
-        if get_time() - self.message_timer < 0.25 {
-            draw_text(&self.message, 20.0, screen_height() - 80.0, 30.0, DARKGREEN);
+        if gamepad::connected_count() > 0 {
+            hud::draw_hammer(self.cursor, GRAY);
         }
-        if self.game_over {
+
+        // Draw score popups and notices
+        self.popups.draw();
+        if !self.clock.is_over() {
+            self.notices.draw(20.0, screen_height() - 80.0);
+        } else {
             draw_text(&format!("Game Over! Final Score: {}", self.score), 20.0, screen_height() - 80.0, 30.0, RED);
         }
 
         draw_text(
-            "Press Q to return to menu, ESC to quit.",
+            &format!(
+                "Press {} to return to menu, {} to pause.",
+                self.bindings.label(Action::Back),
+                self.bindings.label(Action::Pause),
+            ),
             20.0,
             screen_height() - 20.0,
             20.0,
             DARKGRAY,
         );
+
+        if self.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, true);
+        }
     }
 }
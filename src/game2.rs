@@ -1,24 +1,76 @@
 use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
 use std::sync::Arc;
 
-const MOLE_WIDTH: f32 = 256.0;
-const MOLE_HEIGHT: f32 = 256.0;
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::clock::Clock;
+use crate::gamepad::{self, Button};
+use crate::hole;
+use crate::hud::{self, Notices, Popups};
+use crate::input::{self, PressKind};
+use crate::keyboard;
+use crate::layout;
+use crate::settings::{self, Settings};
+use crate::sprite::Sprite;
+
+const PET_BONUS: i32 = 2;
+const CAT_PENALTY: i32 = -5;
+// With lives, spawns get this much quicker every minute survived
+const PACE_PER_MINUTE: f64 = 1.0;
+// Zen: how much each -/+ press changes the spawn rate
+const SPAWN_RATE_STEP: f64 = 0.25;
+// Frenzy at the end of a timed round: empty holes refill this many times as fast,
+// and points are multiplied
+const FRENZY_SPAWN: f64 = 3.0;
+const FRENZY_POINTS: i32 = 2;
+// Waits are drawn to match the original per-frame timing at this frame rate
+const REFERENCE_FPS: f64 = 60.0;
+
+// Which inputs play a board
+#[derive(Clone, Copy, PartialEq)]
+pub enum Seat {
+    Solo,     // Mouse, touch and gamepad, plus hole keys in keyboard mode
+    Mouse,    // Mouse, touch and gamepad only
+    Keyboard, // Hole keys only
+}
 
 pub struct Game {
+    seed: u64,
+    grid: (usize, usize),    // Columns and rows of holes
     moles: Vec<Mole>,
+    spawners: Vec<Spawner>, // One per hole, same order as `moles`
     score: i32,
-    message: String,
-    message_timer: f64,
-    background_texture: Arc<Texture2D>,
-    mole_texture: Arc<Texture2D>,
-    hemlet_mole_texture: Arc<Texture2D>,
-    cat_texture: Arc<Texture2D>,
-    difficulty: u8,
-    start_time: f64,
+    popups: Popups,
+    notices: Notices,
+    assets: Arc<Assets>,
+    hit_leniency: f32,
+    scale: f32,
+    bindings: Bindings,
+    seat: Seat,
+    hole_keys: Vec<Input>,   // Empty unless keyboard mode is on
+    cursor: Vec2,            // Gamepad hammer position
+    camera: Option<Camera2D>, // Set when the board only gets part of the window
+    clock: Clock,
     game_duration: f64,
-    game_over: bool,
+    manual_spawns: bool, // Holes only fill through `pop`, not at random
+    escaped: u32,        // Moles (not cats) that went back down without being hit
+    cats_hit: u32,
+    lives: Option<u32>,   // Survival: no clock, the round ends when these run out
+    target: Option<i32>,  // Time attack: no clock, the round ends on reaching this score
+    zen: bool,            // No clock and no cat penalty, the HUD shows accuracy instead
+    spawn_rate: f64,      // Spawns come this many times as often as usual
+    frenzy_time: f64,     // Seconds at the end of a timed round that are a frenzy
+    frenzy: bool,
+    restartable: bool,    // Whether the pause screen offers Restart
+    swings: u32,          // Whacks and pets, hit or miss
+    hits: u32,            // Swings that landed on something
+    reactions: u32,       // Moles and cats reached, and the seconds it took in total
+    reaction_total: f64,
 }
 
+#[derive(Clone, Copy)]
+#[allow(non_camel_case_types, clippy::enum_variant_names)]
 pub enum Mole {
     Normal_Mole{
         x: f32,
@@ -41,6 +93,66 @@ pub enum Mole {
     },
 }
 
+// What comes out of a hole, without its state
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoleKind {
+    Normal,
+    Helmet,
+    Cat,
+}
+
+// Random source for one hole. Each hole draws its own waits and mole types, so every
+// game started from the same seed gets the same sequence, whatever the frame rate.
+pub struct Spawner {
+    rng: RandGenerator,
+    wait: f64, // Seconds until the hole's next toggle
+    pace: f64, // Waits are divided by this, 1.0 is normal speed
+    mix: (f64, f64), // Chance a new arrival is a normal mole, and a normal or helmet mole
+    frenzy: bool,    // Empty holes refill FRENZY_SPAWN times as fast
+}
+
+impl Spawner {
+    pub fn new(seed: u64, hole: usize) -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(seed ^ (hole as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let wait = reroll_wait(&rng, 0.8, 4.0);
+        Self { rng, wait, pace: 1.0, mix: (0.7, 0.85), frenzy: false }
+    }
+
+    // Draw how long `mole` stays in its new state
+    pub fn next_wait(&mut self, mole: &Mole) {
+        self.wait = match mole {
+            Mole::Helmet_Mole { visible: false, .. } => reroll_wait(&self.rng, 0.5, 3.0), // Moles and cats appear frequency
+            _ => reroll_wait(&self.rng, 0.8, 4.0),
+        } / self.pace;
+        if self.frenzy && !mole.is_visible() {
+            self.wait /= FRENZY_SPAWN;
+        }
+    }
+}
+
+// Holes used to pick a fresh interval in `min..max` every frame and toggle as soon as the
+// time since the last toggle passed it, so most waits ended not long after `min`. At
+// REFERENCE_FPS that is `min` plus a Rayleigh distributed delay, drawn here in one go.
+fn reroll_wait(rng: &RandGenerator, min: f64, max: f64) -> f64 {
+    let sigma = ((max - min) / REFERENCE_FPS).sqrt();
+    let u: f64 = rng.gen_range(0.0, 1.0);
+    (min + sigma * (-2.0 * (1.0 - u).ln()).sqrt()).min(max)
+}
+
+// Send down the moles still up when a round ends. Holes already empty are left alone,
+// hiding them again would restart their sinking animation.
+pub fn hide_all(moles: &mut [Mole], now: f64) {
+    for mole in moles.iter_mut().filter(|mole| mole.is_visible()) {
+        mole.hide_at(now);
+    }
+}
+
+// A seed for a new round. Two draws, as one `rand::rand()` only has 32 bits.
+pub fn random_seed() -> u64 {
+    (rand::rand() as u64) << 32 | rand::rand() as u64
+}
+
 impl Mole {
     pub fn new(x: f32, y: f32) -> Self {
         Mole::Normal_Mole {
@@ -51,126 +163,110 @@ impl Mole {
         }
     }
 
-    pub fn update(&mut self) {
+    // Toggle the mole when its wait is up, drawing what comes next from `spawner`
+    pub fn update(&mut self, spawner: &mut Spawner) {
+        let (visible, last_toggle) = match self {
+            Mole::Normal_Mole { visible, last_toggle, .. } |
+            Mole::Helmet_Mole { visible, last_toggle, .. } |
+            Mole::Cat { visible, last_toggle, .. } => (*visible, *last_toggle),
+        };
+        if get_time() - last_toggle <= spawner.wait {
+            return;
+        }
+
+        if !visible {
+            let random = spawner.rng.gen_range(0.0, 1.0);
+            if random < spawner.mix.0 {   // 70% chance for normal mole by default
+                self.pop_up(MoleKind::Normal);
+            } else if random < spawner.mix.1 { // 15% chance for helmet mole
+                self.pop_up(MoleKind::Helmet);
+            } else {
+                self.pop_up(MoleKind::Cat);     // 15% chance for cat
+            };
+        } else if let Mole::Helmet_Mole { .. } = self {
+            return; // the mole won't disappear unless health reaches 0
+        } else {
+            self.hide();
+        }
+        spawner.next_wait(self);
+    }
+
+    // Come up out of the hole as `kind`
+    pub fn pop_up(&mut self, kind: MoleKind) {
+        let (x, y) = self.position();
+        *self = match kind {
+            MoleKind::Normal => Mole::Normal_Mole {
+                x,
+                y,
+                visible: true,
+                last_toggle: get_time(),
+            },
+            MoleKind::Helmet => Mole::Helmet_Mole {
+                x,
+                y,
+                visible: true,
+                last_toggle: get_time(),
+                health: 3,
+            },
+            MoleKind::Cat => Mole::Cat {
+                x,
+                y,
+                visible: true,
+                last_toggle: get_time(),
+            },
+        };
+    }
+
+    fn sprite<'a>(&self, assets: &'a Assets) -> &'a Sprite {
         match self {
-            Mole::Normal_Mole { x, y, visible, last_toggle } |
-            Mole::Cat { x, y, visible, last_toggle } => {
-                let elapsed = get_time() - *last_toggle;
-                let toggle_interval = rand::gen_range(0.8, 4.0);
-                
-                if elapsed > toggle_interval {
-                    if !*visible {
-                        let random = rand::gen_range(0.0, 1.0);
-                        if random < 0.7 {   // 70% chance for normal mole
-                            *self = Mole::Normal_Mole {
-                                x: *x,
-                                y: *y,
-                                visible: true,
-                                last_toggle: get_time(),
-                            };
-                        } else if random < 0.85 { // 15% chance for helmet mole
-                            *self = Mole::Helmet_Mole {
-                                x: *x,
-                                y: *y,
-                                visible: true,
-                                last_toggle: get_time(),
-                                health: 3,
-                            };
-                        } else {
-                            *self = Mole::Cat {     // 15% chance for cat
-                                x: *x,
-                                y: *y,
-                                visible: true,
-                                last_toggle: get_time(),
-                            };
-                        };
-                    } else {
-                        *visible = false;
-                        *last_toggle = get_time();
-                    }
-                }
-            }
-            Mole::Helmet_Mole { x, y, visible, last_toggle, health } => {
-                if !*visible {
-                    let elapsed = get_time() - *last_toggle;
-                    let toggle_interval = rand::gen_range(0.5, 3.0); // Moles and cats appear frequency
-                    
-                    if elapsed > toggle_interval {
-                        if !*visible {
-                            let random = rand::gen_range(0.0, 1.0); // Same spawning logic
-                            if random < 0.7 {
-                                *self = Mole::Normal_Mole {
-                                    x: *x,
-                                    y: *y,
-                                    visible: true,
-                                    last_toggle: get_time(),
-                                };
-                            } else if random < 0.85 {
-                                *self = Mole::Helmet_Mole {
-                                    x: *x,
-                                    y: *y,
-                                    visible: true,
-                                    last_toggle: get_time(),
-                                    health: 3,
-                                };
-                            } else {
-                                *self = Mole::Cat {
-                                    x: *x,
-                                    y: *y,
-                                    visible: true,
-                                    last_toggle: get_time(),
-                                };
-                            };
-                        }
-                    }   // the mole won't disappear unless health reaches 0
-                }
-            }
+            Mole::Normal_Mole { .. } => &assets.mole,
+            Mole::Helmet_Mole { .. } => &assets.helmet_mole,
+            Mole::Cat { .. } => &assets.cat,
         }
     }
 
-    pub fn draw(&self, 
-        mole_texture: &Arc<Texture2D>,
-        hemlet_mole_texture: &Arc<Texture2D>,
-        cat_texture: &Arc<Texture2D>,
-    ) {
+    pub fn is_visible(&self) -> bool {
         match self {
-            Mole::Normal_Mole { x, y, visible, .. } => {
-                if *visible {
-                    // Draw mole image
-                    draw_texture(
-                        mole_texture,
-                        *x - MOLE_WIDTH / 2.0,
-                        *y - MOLE_HEIGHT / 2.0,
-                        WHITE,
-                    );
-                }
-            }
-            Mole::Helmet_Mole { x, y, visible, .. } => {
-                if *visible {
-                    // Draw helmet mole image
-                    draw_texture(
-                        hemlet_mole_texture,
-                        *x - MOLE_WIDTH / 2.0,
-                        *y - MOLE_HEIGHT / 2.0,
-                WHITE,
-            );
-                }
-            }
-            Mole::Cat { x, y, visible, .. } => {
-                if *visible {
-                    // Draw cat image
-                    draw_texture(
-                        cat_texture,
-                        *x - MOLE_WIDTH / 2.0,
-                        *y - MOLE_HEIGHT / 2.0,
-                        WHITE,
-                    );
-                }
-            }
+            Mole::Normal_Mole { visible, .. } |
+            Mole::Helmet_Mole { visible, .. } |
+            Mole::Cat { visible, .. } => *visible,
         }
     }
 
-    pub fn is_clicked(&self, mouse_x: f32, mouse_y: f32) -> bool {
+    pub fn position(&self) -> (f32, f32) {
+        match self {
+            Mole::Normal_Mole { x, y, .. } |
+            Mole::Helmet_Mole { x, y, .. } |
+            Mole::Cat { x, y, .. } => (*x, *y),
+        }
+    }
+
+    // How far the mole is out of its hole, animating the last appear/disappear
+    fn rise(&self) -> f32 {
+        self.rise_at(get_time())
+    }
+
+    fn rise_at(&self, now: f64) -> f32 {
+        match self {
+            Mole::Normal_Mole { visible, last_toggle, .. } |
+            Mole::Helmet_Mole { visible, last_toggle, .. } |
+            Mole::Cat { visible, last_toggle, .. } => hole::rise(*visible, now - *last_toggle),
+        }
+    }
+
+    pub fn draw(&self, assets: &Assets, scale: f32) {
+        // Draw the hole with the mole, helmet mole or cat rising out of it
+        let (x, y) = self.position();
+        hole::draw(assets, self.sprite(assets), vec2(x, y), scale, self.rise());
+    }
+
+    // Draw `text` on the mole, rising and sinking with it, the first `typed` characters done
+    pub fn draw_badge(&self, assets: &Assets, scale: f32, text: &str, typed: usize) {
+        let (x, y) = self.position();
+        hole::draw_badge(self.sprite(assets), vec2(x, y), scale, self.rise(), text, typed);
+    }
+
+    pub fn is_clicked(&self, mouse_x: f32, mouse_y: f32, assets: &Assets, scale: f32, leniency: f32) -> bool {
         let (x, y, visible) = match self {
             Mole::Normal_Mole { x, y, visible, .. } |
             Mole::Helmet_Mole { x, y, visible, .. } |
@@ -180,136 +276,592 @@ impl Mole {
         if !visible {
             return false;
         }
-        // Rectangle collision detection
-        let left = x - MOLE_WIDTH / 2.0;
-        let right = x + MOLE_WIDTH / 2.0;
-        let top = y - MOLE_HEIGHT / 2.0;
-        let bottom = y + MOLE_HEIGHT / 2.0;
-        
-        mouse_x >= left && mouse_x <= right && mouse_y >= top && mouse_y <= bottom
+        // Only the opaque part of the sprite above ground counts
+        hole::contains(self.sprite(assets), vec2(x, y), scale, self.rise(), vec2(mouse_x, mouse_y), leniency)
     }
 
     pub fn hide(&mut self) {
+        self.hide_at(get_time());
+    }
+
+    fn hide_at(&mut self, now: f64) {
         match self {
             Mole::Normal_Mole { visible, last_toggle, .. } |
             Mole::Helmet_Mole { visible, last_toggle, .. } |
             Mole::Cat { visible, last_toggle, .. } => {
                 *visible = false;
-                *last_toggle = get_time();
+                *last_toggle = now;
             }
         }
     }
+
+    // What is up in the hole and its helmet health, None if the hole is empty
+    fn target(&self) -> Option<(MoleKind, u8)> {
+        match self {
+            Mole::Normal_Mole { visible: true, .. } => Some((MoleKind::Normal, 0)),
+            Mole::Helmet_Mole { visible: true, health, .. } => Some((MoleKind::Helmet, *health)),
+            Mole::Cat { visible: true, .. } => Some((MoleKind::Cat, 0)),
+            _ => None,
+        }
+    }
+
+    // When the mole last came up or went down
+    fn up_since(&self) -> f64 {
+        match self {
+            Mole::Normal_Mole { last_toggle, .. } |
+            Mole::Helmet_Mole { last_toggle, .. } |
+            Mole::Cat { last_toggle, .. } => *last_toggle,
+        }
+    }
+
+    // Push the mole's timer forward, e.g. after the game was paused
+    pub fn delay(&mut self, seconds: f64) {
+        match self {
+            Mole::Normal_Mole { last_toggle, .. } |
+            Mole::Helmet_Mole { last_toggle, .. } |
+            Mole::Cat { last_toggle, .. } => *last_toggle += seconds,
+        }
+    }
+}
+
+// What a whack or a pet does to whatever is in a hole
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Strike {
+    Miss,              // Nothing was up
+    Whacked(i32),      // A mole went down for these points
+    HelmetBroken(i32), // A helmet mole's last hit, for these points
+    Clank,             // A helmet took the hit and stays up
+    CatHit,            // CAT_PENALTY, and a life in survival
+    CatLeft,           // Zen: the cat just leaves
+    Petted(i32),       // A petted cat leaves with this bonus
+    PettedMole,        // Moles don't care about being petted
+}
+
+impl Strike {
+    fn points(self) -> i32 {
+        match self {
+            Strike::Whacked(points) | Strike::HelmetBroken(points) | Strike::Petted(points) => points,
+            Strike::CatHit => CAT_PENALTY,
+            Strike::Miss | Strike::Clank | Strike::CatLeft | Strike::PettedMole => 0,
+        }
+    }
+}
+
+// A press on a hole holding `target` (kind and helmet health, None if nothing is up).
+// Points are multiplied during a frenzy, penalties aren't.
+fn strike(press: PressKind, target: Option<(MoleKind, u8)>, zen: bool, multiplier: i32) -> Strike {
+    let Some((kind, health)) = target else {
+        return Strike::Miss;
+    };
+    match (press, kind) {
+        (PressKind::Primary, MoleKind::Normal) => Strike::Whacked(multiplier), // Normal mole hit +1 score
+        (PressKind::Primary, MoleKind::Helmet) if health > 1 => Strike::Clank, // Helmet mole hit -1 health
+        (PressKind::Primary, MoleKind::Helmet) => Strike::HelmetBroken(2 * multiplier), // 0 health +2 score
+        (PressKind::Primary, MoleKind::Cat) if zen => Strike::CatLeft,
+        (PressKind::Primary, MoleKind::Cat) => Strike::CatHit, // Cat hit -5 score
+        (PressKind::Secondary, MoleKind::Cat) => Strike::Petted(PET_BONUS * multiplier),
+        (PressKind::Secondary, _) => Strike::PettedMole,
+    }
 }
 
 impl Game {
     pub fn new(
         difficulty: u8,
-        background_texture: Arc<Texture2D>,
-        mole_texture: Arc<Texture2D>,
-        hemlet_mole_texture: Arc<Texture2D>,
-        cat_texture: Arc<Texture2D>,
+        assets: Arc<Assets>,
+        settings: &Settings,
+        bindings: &Bindings,
     ) -> Self {
-        // Positions of four moles
-        let positions = vec![
-            (450.0, 200.0),  // Top left
-            (850.0, 200.0),  // Top right
-            (450.0, 500.0),  // Bottom left
-            (850.0, 500.0),  // Bottom right
-        ];
+        Self::with_seed(difficulty, assets, settings, bindings, random_seed())
+    }
+
+    // A game whose moles pop up in the same order as any other game with this seed
+    pub fn with_seed(
+        difficulty: u8,
+        assets: Arc<Assets>,
+        settings: &Settings,
+        bindings: &Bindings,
+        seed: u64,
+    ) -> Self {
+        // Positions of four moles in a 2x2 grid
+        let scale = settings.medium_scale;
+        let positions = layout::grid(2, 2, scale);
 
         let moles = positions
             .iter()
             .map(|&(x, y)| Mole::new(x, y))
             .collect();
+        let spawners = (0..positions.len()).map(|i| Spawner::new(seed, i)).collect();
+
+        let mut notices = Notices::new();
+        notices.push(&format!("Difficulty: {} - Click the moles!", difficulty), DARKGREEN);
 
         Self {
+            seed,
+            grid: (2, 2),
             moles,
+            spawners,
             score: 0,
-            message: format!("Difficulty: {} - Click the moles!", difficulty),
-            message_timer: 0.0,
-            background_texture,
-            mole_texture,
-            hemlet_mole_texture,
-            cat_texture,
-            difficulty,
-            start_time: get_time(),
+            popups: Popups::new(),
+            notices,
+            assets,
+            hit_leniency: settings.hit_leniency,
+            scale,
+            bindings: bindings.clone(),
+            seat: Seat::Solo,
+            hole_keys: if settings.keyboard_mode { keyboard::hole_inputs(2, 2, bindings) } else { Vec::new() },
+            cursor: vec2(screen_width() / 2.0, screen_height() / 2.0),
+            camera: None,
+            clock: Clock::start(),
             game_duration: 60.0,
-            game_over: false,
+            manual_spawns: false,
+            escaped: 0,
+            cats_hit: 0,
+            lives: None,
+            target: None,
+            zen: false,
+            spawn_rate: 1.0,
+            frenzy_time: settings.frenzy_seconds as f64,
+            frenzy: false,
+            restartable: true,
+            swings: 0,
+            hits: 0,
+            reactions: 0,
+            reaction_total: 0.0,
         }
     }
 
+    // Hand the board to one player's inputs, for games with two boards at once
+    pub fn with_seat(mut self, seat: Seat) -> Self {
+        self.seat = seat;
+        self.hole_keys = match seat {
+            Seat::Solo => self.hole_keys,
+            Seat::Mouse => Vec::new(),
+            Seat::Keyboard => keyboard::hole_inputs(self.grid.0, self.grid.1, &self.bindings),
+        };
+        self
+    }
+
+    // Lay the holes out `cols` x `rows` instead of 2x2, shrinking the moles to fit
+    pub fn with_grid(mut self, cols: usize, rows: usize) -> Self {
+        // Up to two holes across and down keep full size, bigger boards shrink
+        let span = cols.max(rows).saturating_sub(1).max(1);
+        let fit = (1.4 / span as f32).min(1.0);
+        self.scale *= fit;
+        self.grid = (cols, rows);
+        let mix = self.spawners[0].mix;
+        let positions = layout::grid(cols, rows, self.scale);
+        self.moles = positions.iter().map(|&(x, y)| Mole::new(x, y)).collect();
+        self.spawners = (0..positions.len()).map(|i| Spawner::new(self.seed, i)).collect();
+        for spawner in &mut self.spawners {
+            spawner.mix = mix;
+        }
+        if !self.hole_keys.is_empty() {
+            self.hole_keys = keyboard::hole_inputs(cols, rows, &self.bindings);
+        }
+        self
+    }
+
+    // Draw the whole board scaled into `viewport` (window pixels), e.g. one half of a split screen
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        let mut camera = Camera2D {
+            target: vec2(screen_width() / 2.0, screen_height() / 2.0),
+            zoom: vec2(2.0 / screen_width(), 2.0 / screen_height()),
+            ..Default::default()
+        };
+        // GL viewports count from the bottom of the window
+        camera.viewport = Some((
+            viewport.x as i32,
+            (screen_height() - viewport.y - viewport.h) as i32,
+            viewport.w as i32,
+            viewport.h as i32,
+        ));
+        self.camera = Some(camera);
+    }
+
+    // Make the round last `seconds` instead of a minute
+    pub fn with_duration(mut self, seconds: f64) -> Self {
+        self.game_duration = seconds;
+        self
+    }
+
+    // Survival: play until `lives` are lost instead of against the clock. A normal mole
+    // that gets away or a hit cat costs a life, and the moles keep speeding up.
+    pub fn with_lives(mut self, lives: u32) -> Self {
+        self.lives = Some(lives);
+        self.game_duration = f64::INFINITY;
+        self
+    }
+
+    // Time attack: play until the score reaches `target`, the result is how long it took
+    pub fn with_target(mut self, target: i32) -> Self {
+        self.target = Some(target);
+        self.game_duration = f64::INFINITY;
+        self
+    }
+
+    pub fn spawn_rate(&self) -> f64 {
+        self.spawn_rate
+    }
+
+    // Spawn moles `rate` times as often as usual
+    pub fn with_spawn_rate(mut self, rate: f64) -> Self {
+        self.spawn_rate = rate;
+        self
+    }
+
+    // For modes that can't start a round over (hot-seat turns, LAN rounds), so pausing
+    // doesn't offer it
+    pub fn without_restart(mut self) -> Self {
+        self.restartable = false;
+        self
+    }
+
+    // Make the last `seconds` of the round a frenzy, 0 for none
+    pub fn with_frenzy(mut self, seconds: f32) -> Self {
+        self.frenzy_time = seconds as f64;
+        self
+    }
+
+    // Change what comes up: `normal`, `helmet` and the rest cats, as chances out of 1.0
+    pub fn with_mix(mut self, normal: f64, helmet: f64) -> Self {
+        for spawner in &mut self.spawners {
+            spawner.mix = (normal, normal + helmet);
+        }
+        self
+    }
+
+    // Zen: play without a clock or cat penalty, with spawns `spawn_rate` times as often.
+    // -/+ change the rate during the round.
+    pub fn with_zen(mut self, spawn_rate: f32) -> Self {
+        self.zen = true;
+        self.spawn_rate = spawn_rate as f64;
+        self.game_duration = f64::INFINITY;
+        self
+    }
+
+    // Leave the holes empty until another player fills them with `pop`
+    pub fn with_manual_spawns(mut self) -> Self {
+        self.manual_spawns = true;
+        self
+    }
+
+    // Bring `kind` up in hole `index` if the hole is empty. Returns whether it came up.
+    pub fn pop(&mut self, index: usize, kind: MoleKind) -> bool {
+        let mole = &mut self.moles[index];
+        if !self.clock.is_running() || mole.is_visible() {
+            return false;
+        }
+        mole.pop_up(kind);
+        self.spawners[index].next_wait(mole);
+        true
+    }
+
     pub fn update(&mut self) -> bool {
-        // Update all moles
-        for mole in &mut self.moles {
-            if self.game_over {
-                mole.hide();
+        let back_to_menu = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        // Pause or resume, the round clock doesn't run while paused
+        if self.bindings.pressed(Action::Pause) || gamepad::is_button_pressed(Button::Start) {
+            self.toggle_pause();
+        }
+        if self.clock.is_paused() {
+            return back_to_menu;
+        }
+
+        // Zen lets the player change the spawn rate on the fly
+        if self.zen {
+            let step = if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+                SPAWN_RATE_STEP
+            } else if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+                -SPAWN_RATE_STEP
             } else {
-                mole.update();
+                0.0
+            };
+            if step != 0.0 {
+                let (min, max) = (settings::MIN_SPAWN_RATE as f64, settings::MAX_SPAWN_RATE as f64);
+                self.spawn_rate = (self.spawn_rate + step).clamp(min, max);
+                self.notices.push(&format!("Spawn rate: {:.2}x", self.spawn_rate), DARKGREEN);
             }
         }
 
-        // Handle mouse clicks
-        if is_mouse_button_pressed(MouseButton::Left) {
-            let (mouse_x, mouse_y) = mouse_position();
-            let mut hit = false;
-
-            for mole in &mut self.moles {
-                if mole.is_clicked(mouse_x, mouse_y) {
-                    match mole {
-                        Mole::Normal_Mole { .. } => {   // Normal mole hit +1 score
-                            self.score += 1;
-                            mole.hide();
-                            self.message = format!("Hit! +1 Score: {}", self.score);
-                            self.message_timer = get_time();
-                            hit = true;
-                        }
-                        Mole::Helmet_Mole { health, .. } => {   // Helmet mole hit -1 health, if 0 health +2 score
-                            *health -= 1;
-                            if *health == 0 {
-                                self.score += 2;
-                                mole.hide();
-                                self.message = format!("Helmet Broken! +2 Score: {}", self.score);
-                                hit = true;
-                            } else {
-                                self.message = "Helmet Protected!".to_string();
-                                hit = true;
-                            }
-                            self.message_timer = get_time();
-                        }
-                        Mole::Cat { .. } => {   // Cat hit -5 score
-                            self.score -= 5;
-                            mole.hide();
-                            self.message = format!("Don't hit cats! -5 Score: {}", self.score);
-                            self.message_timer = get_time();
-                            hit = true;
-                        }
-                    }
-                    break;
+        // The last seconds of a timed round are a frenzy: quicker spawns, double points
+        let remaining = self.game_duration - self.elapsed();
+        if !self.frenzy && !self.clock.is_over() && self.frenzy_time > 0.0 && remaining <= self.frenzy_time {
+            self.frenzy = true;
+            self.notices.push(&format!("FRENZY! {}x points!", FRENZY_POINTS), ORANGE);
+            for (mole, spawner) in self.moles.iter().zip(&mut self.spawners) {
+                spawner.frenzy = true;
+                if !mole.is_visible() {
+                    spawner.wait /= FRENZY_SPAWN;
                 }
             }
+        }
 
-            if !hit {
-                self.message = "Missed!".to_string();
-                self.message_timer = get_time();
+        // Update all moles
+        let speed_up = if self.lives.is_some() { 1.0 + self.elapsed() / 60.0 * PACE_PER_MINUTE } else { 1.0 };
+        let pace = self.spawn_rate * speed_up;
+        let mut escaped = 0;
+        for (mole, spawner) in self.moles.iter_mut().zip(&mut self.spawners) {
+            spawner.pace = pace;
+            // Once the round is over the board stays as end() left it
+            if !self.clock.is_over() && (!self.manual_spawns || mole.is_visible()) {
+                let was_visible = mole.is_visible();
+                mole.update(spawner);
+                if was_visible && !mole.is_visible() && !matches!(mole, Mole::Cat { .. }) {
+                    escaped += 1;
+                }
             }
         }
+        self.escaped += escaped;
+        for _ in 0..escaped {
+            self.lose_life("A mole got away!");
+        }
+
+        // Handle every click and touch since last frame, in order
+        let presses = if self.seat == Seat::Keyboard { Vec::new() } else { input::presses() };
+        for press in presses {
+            // Clicks are in window pixels, the board may only cover part of the window
+            let point = match &self.camera {
+                Some(camera) => {
+                    let (vx, vy, vw, vh) = camera.viewport.unwrap_or_default();
+                    let on_board = Rect::new(vx as f32, screen_height() - (vy + vh) as f32, vw as f32, vh as f32);
+                    if !on_board.contains(vec2(press.x, press.y)) {
+                        continue;
+                    }
+                    camera.screen_to_world(vec2(press.x, press.y))
+                }
+                None => vec2(press.x, press.y),
+            };
+            self.strike_at(press.kind, point.x, point.y);
+        }
+
+        // Handle hole keys in keyboard mode, holding Shift pets instead of whacking
+        if let Some(index) = keyboard::pressed_hole(&self.hole_keys) {
+            let (x, y) = self.moles[index].position();
+            let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+            let press = if shift { PressKind::Secondary } else { PressKind::Primary };
+            self.strike_hole(index, press, x, y);
+        }
         
+        // Handle the gamepad hammer
+        if self.seat != Seat::Keyboard && gamepad::connected_count() > 0 {
+            self.cursor = hud::move_cursor(self.cursor, gamepad::left_stick());
+            if gamepad::is_button_pressed(Button::A) {
+                self.whack(self.cursor.x, self.cursor.y);
+            }
+            if gamepad::is_button_pressed(Button::X) {
+                self.pet(self.cursor.x, self.cursor.y);
+            }
+        }
+
+        self.popups.update();
+        self.notices.update();
+
         // Check for game over
-        if get_time() - self.start_time >= self.game_duration { 
-            self.game_over = true;
+        let reached_target = self.target.is_some_and(|target| self.score >= target);
+        if self.elapsed() >= self.game_duration || reached_target {
+            self.end();
         }
 
         // Check for return to menu
-        is_key_pressed(KeyCode::Q)
+        back_to_menu
+    }
+
+    // Seconds the round has been running, not counting pauses
+    pub fn elapsed(&self) -> f64 {
+        self.clock.elapsed()
+    }
+
+    fn end(&mut self) {
+        if !self.clock.is_over() {
+            self.clock.end();
+            hide_all(&mut self.moles, get_time());
+        }
+    }
+
+    // Survival only: take a life, ending the round on the last one
+    fn lose_life(&mut self, reason: &str) {
+        let Some(lives) = &mut self.lives else {
+            return;
+        };
+        *lives = lives.saturating_sub(1);
+        if *lives == 0 {
+            self.end();
+        } else {
+            self.notices.push(&format!("{} {} left", reason, lives), RED);
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        // Push every timer forward by the pause so nothing jumps on resume
+        if let Some(paused_for) = self.clock.toggle_pause() {
+            for mole in &mut self.moles {
+                mole.delay(paused_for);
+            }
+        }
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.clock.is_over()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.clock.is_paused()
+    }
+
+    pub fn escaped(&self) -> u32 {
+        self.escaped
+    }
+
+    pub fn cats_hit(&self) -> u32 {
+        self.cats_hit
+    }
+
+    // Points are multiplied during a frenzy, penalties aren't
+    fn multiplier(&self) -> i32 {
+        if self.frenzy { FRENZY_POINTS } else { 1 }
+    }
+
+    // Share of swings that landed, once there has been one
+    fn accuracy(&self) -> Option<f64> {
+        (self.swings > 0).then(|| self.hits as f64 / self.swings as f64)
+    }
+
+    // Average seconds from a mole or cat coming up to the first swing that reached it
+    fn reaction_time(&self) -> Option<f64> {
+        (self.reactions > 0).then(|| self.reaction_total / self.reactions as f64)
+    }
+
+    // Count a swing, and how long the player took to reach `index` if it landed there first
+    fn track_swing(&mut self, index: Option<usize>) {
+        self.swings += 1;
+        let Some(index) = index else {
+            return;
+        };
+        let mole = &self.moles[index];
+        if !mole.is_visible() {
+            return;
+        }
+        self.hits += 1;
+        // A helmet mole takes three hits, only the first one counts as reacting
+        if !matches!(mole, Mole::Helmet_Mole { health, .. } if *health < 3) {
+            self.reactions += 1;
+            self.reaction_total += get_time() - mole.up_since();
+        }
+    }
+
+    // Draw `labels` under the holes, in hole order
+    pub fn draw_hole_labels(&self, labels: &[String]) {
+        if let Some(camera) = &self.camera {
+            set_camera(camera);
+        }
+        for (mole, label) in self.moles.iter().zip(labels) {
+            let (x, y) = mole.position();
+            hole::draw_label(&self.assets.mole, vec2(x, y), self.scale, label);
+        }
+        if self.camera.is_some() {
+            set_default_camera();
+        }
+    }
+
+    // Index of the visible mole under the point, if any
+    fn mole_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.moles
+            .iter()
+            .position(|mole| mole.is_clicked(x, y, &self.assets, self.scale, self.hit_leniency))
+    }
+
+    // Left click: whack whatever is under the cursor
+    fn whack(&mut self, mouse_x: f32, mouse_y: f32) {
+        self.strike_at(PressKind::Primary, mouse_x, mouse_y);
+    }
+
+    // Pet cats for a small bonus, moles don't care about being petted
+    fn pet(&mut self, mouse_x: f32, mouse_y: f32) {
+        self.strike_at(PressKind::Secondary, mouse_x, mouse_y);
+    }
+
+    // Whack or pet whatever is under (x, y)
+    fn strike_at(&mut self, press: PressKind, x: f32, y: f32) {
+        match self.mole_at(x, y) {
+            Some(index) => self.strike_hole(index, press, x, y),
+            None => {
+                self.track_swing(None);
+                self.popups.spawn("Miss", x, y, LIGHTGRAY);
+            }
+        }
+    }
+
+    // Whack or pet whatever is in hole `index`, showing the result at (x, y)
+    fn strike_hole(&mut self, index: usize, press: PressKind, x: f32, y: f32) {
+        self.track_swing(Some(index));
+        let result = strike(press, self.moles[index].target(), self.zen, self.multiplier());
+        let mole = &mut self.moles[index];
+        if let Mole::Helmet_Mole { health, .. } = mole {
+            if matches!(result, Strike::Clank | Strike::HelmetBroken(_)) {
+                *health -= 1;
+            }
+        }
+        if !matches!(result, Strike::Miss | Strike::PettedMole | Strike::Clank) {
+            mole.hide();
+            self.spawners[index].next_wait(mole);
+        }
+        self.score += result.points();
+
+        match result {
+            Strike::Miss => self.popups.spawn("Miss", x, y, LIGHTGRAY),
+            Strike::Whacked(points) => self.popups.spawn(&format!("+{}", points), x, y, GREEN),
+            Strike::HelmetBroken(points) => {
+                self.popups.spawn(&format!("+{}", points), x, y, GOLD);
+                self.notices.push("Helmet Broken!", DARKGREEN);
+            }
+            Strike::Clank => self.popups.spawn("Clank!", x, y, LIGHTGRAY),
+            Strike::CatLeft => self.popups.spawn("Meow!", x, y, PINK),
+            Strike::CatHit => {
+                self.cats_hit += 1;
+                self.popups.spawn(&format!("{}", CAT_PENALTY), x, y, RED);
+                self.notices.push("Don't hit cats!", RED);
+                self.lose_life("Ouch, a cat!");
+            }
+            Strike::Petted(points) => self.popups.spawn(&format!("Purr~ +{}", points), x, y, PINK),
+            Strike::PettedMole => {
+                self.popups.spawn("Miss", x, y, LIGHTGRAY);
+                self.notices.push("Moles need a whack, not a pet!", DARKGRAY);
+            }
+        }
     }
 
     pub fn draw(&self) {
+        if let Some(camera) = &self.camera {
+            set_camera(camera);
+        }
+
         // Draw background
-        draw_texture(&self.background_texture, 0.0, 0.0, WHITE);
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
+
+        // Draw all holes from the back row forward so lower holes overlap upper ones
+        let mut moles: Vec<&Mole> = self.moles.iter().collect();
+        moles.sort_by(|a, b| a.position().1.total_cmp(&b.position().1));
+        for mole in moles {
+            mole.draw(&self.assets, self.scale);
+        }
 
-        // Draw all moles
-        for mole in &self.moles {
-            mole.draw(&self.mole_texture, &self.hemlet_mole_texture, &self.cat_texture);
+        // Draw each hole's key in keyboard mode
+        for (mole, &input) in self.moles.iter().zip(&self.hole_keys) {
+            let (x, y) = mole.position();
+            hole::draw_label(&self.assets.mole, vec2(x, y), self.scale, &keyboard::key_label(input));
+        }
+
+        // Tint the board and announce the frenzy, pulsing
+        if self.frenzy && !self.clock.is_over() {
+            let pulse = 0.12 + 0.06 * (get_time() * 8.0).sin() as f32;
+            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(1.0, 0.3, 0.0, pulse));
+            let banner = format!("FRENZY x{}", FRENZY_POINTS);
+            let width = measure_text(&banner, None, 60, 1.0).width;
+            draw_text(&banner, (screen_width() - width) / 2.0, 60.0, 60.0, ORANGE);
         }
 
         // Draw UI
@@ -320,9 +872,41 @@ impl Game {
             40.0,
             WHITE,
         );
-        if !self.game_over {
+        if self.zen {
+            let accuracy = match self.accuracy() {
+                Some(accuracy) => format!("{:.0}%", accuracy * 100.0),
+                None => "-".to_string(),
+            };
+            let reaction = match self.reaction_time() {
+                Some(seconds) => format!("{:.2}s", seconds),
+                None => "-".to_string(),
+            };
             draw_text(
-                &format!("Time: {:.0}", self.game_duration - (get_time() - self.start_time)),
+                &format!("Accuracy: {}   Reaction: {}", accuracy, reaction),
+                20.0,
+                80.0,
+                40.0,
+                WHITE,
+            );
+        } else if let Some(target) = self.target {
+            draw_text(
+                &format!("Time: {:.1}   Goal: {}", self.elapsed(), target),
+                20.0,
+                80.0,
+                40.0,
+                WHITE,
+            );
+        } else if let Some(lives) = self.lives {
+            draw_text(
+                &format!("Time: {:.0}   Lives: {}", self.elapsed(), lives),
+                20.0,
+                80.0,
+                40.0,
+                WHITE,
+            );
+        } else if !self.clock.is_over() {
+            draw_text(
+                &format!("Time: {:.0}", self.game_duration - self.elapsed()),
                 20.0,
                 80.0,
                 40.0,
@@ -337,20 +921,167 @@ impl Game {
                 WHITE,
             );
         }
-        // Draw message (disappears after 0.25 seconds)
-        if get_time() - self.message_timer < 0.25 {
-            draw_text(&self.message, 20.0, screen_height() - 80.0, 30.0, DARKGREEN);
+        if self.seat != Seat::Keyboard && gamepad::connected_count() > 0 {
+            hud::draw_hammer(self.cursor, GRAY);
         }
-        if self.game_over {
+
+        // Draw score popups and notices
+        self.popups.draw();
+        if !self.clock.is_over() {
+            self.notices.draw(20.0, screen_height() - 80.0);
+        } else {
             draw_text(&format!("Game Over! Final Score: {}", self.score), 20.0, screen_height() - 80.0, 30.0, RED);
         }
 
+        let pet_hint = if self.seat == Seat::Keyboard {
+            format!("Press Shift + a cat's key to pet it (+{}).", PET_BONUS)
+        } else if self.hole_keys.is_empty() {
+            format!("Pet a cat with {} (+{}).", self.bindings.label(Action::Pet), PET_BONUS)
+        } else {
+            format!("Pet a cat with {} or Shift + its key (+{}).", self.bindings.label(Action::Pet), PET_BONUS)
+        };
         draw_text(
-            "Press Q to return to menu, ESC to quit.",
+            &pet_hint,
+            20.0,
+            screen_height() - 45.0,
+            20.0,
+            DARKGRAY,
+        );
+        let spawn_hint = if self.zen {
+            format!(" -/+ change the spawn rate ({:.2}x).", self.spawn_rate)
+        } else {
+            String::new()
+        };
+        draw_text(
+            &format!(
+                "Press {} to return to menu, {} to pause.{}",
+                self.bindings.label(Action::Back),
+                self.bindings.label(Action::Pause),
+                spawn_hint,
+            ),
             20.0,
             screen_height() - 20.0,
             20.0,
             DARKGRAY,
         );
+
+        if self.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, self.restartable);
+        }
+
+        if self.camera.is_some() {
+            set_default_camera();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_seed_replays_the_same_waits() {
+        let mole = Mole::Normal_Mole { x: 0.0, y: 0.0, visible: true, last_toggle: 0.0 };
+        let waits = |seed, hole| {
+            let mut spawner = Spawner::new(seed, hole);
+            (0..20)
+                .map(|_| {
+                    spawner.next_wait(&mole);
+                    spawner.wait
+                })
+                .collect::<Vec<f64>>()
+        };
+
+        assert_eq!(waits(7, 0), waits(7, 0));
+        assert_ne!(waits(7, 0), waits(7, 1));
+        assert_ne!(waits(7, 0), waits(8, 0));
+    }
+
+    #[test]
+    fn holes_already_empty_stay_down_when_the_round_ends() {
+        let mut moles = vec![
+            Mole::Normal_Mole { x: 0.0, y: 0.0, visible: false, last_toggle: 0.0 },
+            Mole::Cat { x: 0.0, y: 0.0, visible: true, last_toggle: 0.0 },
+        ];
+        hide_all(&mut moles, 10.0);
+        assert_eq!(moles[0].rise_at(10.0), 0.0);
+        // The one that was up sinks from where it was
+        assert!(!moles[1].is_visible());
+        assert_eq!(moles[1].rise_at(10.0), 1.0);
+        assert_eq!(moles[1].rise_at(20.0), 0.0);
+    }
+
+    #[test]
+    fn waits_match_the_per_frame_reroll() {
+        // The original timing: every frame at REFERENCE_FPS, toggle once the time since
+        // the last toggle is past a fresh random interval
+        let rng = RandGenerator::new();
+        rng.srand(3);
+        let old_wait = || {
+            let mut elapsed = 0.0;
+            loop {
+                elapsed += 1.0 / REFERENCE_FPS;
+                if elapsed > rng.gen_range(0.8, 4.0) {
+                    return elapsed;
+                }
+            }
+        };
+        let runs = 20_000;
+        let old_average = (0..runs).map(|_| old_wait()).sum::<f64>() / runs as f64;
+
+        let mole = Mole::Normal_Mole { x: 0.0, y: 0.0, visible: true, last_toggle: 0.0 };
+        let mut spawner = Spawner::new(5, 0);
+        let new_average = (0..runs)
+            .map(|_| {
+                spawner.next_wait(&mole);
+                spawner.wait
+            })
+            .sum::<f64>()
+            / runs as f64;
+
+        assert!((old_average - 1.09).abs() < 0.03, "old average {}", old_average);
+        assert!((new_average - old_average).abs() < 0.03, "{} vs {}", new_average, old_average);
+    }
+
+    #[test]
+    fn each_press_kind_scores_by_what_is_in_the_hole() {
+        // A mole, a cat, a helmet mole on its last hit, nothing
+        let holes = [
+            Some((MoleKind::Normal, 0)),
+            Some((MoleKind::Cat, 0)),
+            Some((MoleKind::Helmet, 1)),
+            None,
+        ];
+        let presses = [
+            (PressKind::Primary, 0),
+            (PressKind::Secondary, 1),
+            (PressKind::Primary, 1),
+            (PressKind::Primary, 2),
+            (PressKind::Secondary, 0),
+            (PressKind::Primary, 3),
+        ];
+
+        let strikes: Vec<Strike> = presses
+            .iter()
+            .map(|&(kind, hole)| strike(kind, holes[hole], false, 1))
+            .collect();
+        assert_eq!(
+            strikes,
+            [
+                Strike::Whacked(1),
+                Strike::Petted(PET_BONUS),
+                Strike::CatHit,
+                Strike::HelmetBroken(2),
+                Strike::PettedMole,
+                Strike::Miss,
+            ]
+        );
+        assert_eq!(strikes.iter().map(|strike| strike.points()).sum::<i32>(), 1 + PET_BONUS + CAT_PENALTY + 2);
+
+        // A frenzy doubles points but not the cat penalty, zen drops it
+        assert_eq!(strike(PressKind::Primary, holes[0], false, FRENZY_POINTS), Strike::Whacked(FRENZY_POINTS));
+        assert_eq!(strike(PressKind::Primary, holes[1], false, FRENZY_POINTS).points(), CAT_PENALTY);
+        assert_eq!(strike(PressKind::Primary, holes[1], true, 1), Strike::CatLeft);
+        assert_eq!(strike(PressKind::Primary, Some((MoleKind::Helmet, 3)), false, 1), Strike::Clank);
     }
 }
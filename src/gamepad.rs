@@ -0,0 +1,241 @@
+use macroquad::prelude::*;
+use std::cell::RefCell;
+
+// Gamepad input, polled once per frame with `update()` and read like macroquad's
+// keyboard functions. Buttons and axes follow the Xbox layout reported by Linux's
+// joystick driver (/dev/input/js*); other platforms see no gamepads.
+
+// Stick deflection below this is ignored
+const DEAD_ZONE: f32 = 0.3;
+// Seconds between scans for newly plugged in gamepads
+const SCAN_INTERVAL: f64 = 1.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Button {
+    A,
+    B,
+    X,
+    Back,
+    Start,
+}
+
+impl Button {
+    fn index(self) -> usize {
+        match self {
+            Button::A => 0,
+            Button::B => 1,
+            Button::X => 2,
+            Button::Back => 6,
+            Button::Start => 7,
+        }
+    }
+}
+
+const MAX_BUTTONS: usize = 16;
+const MAX_AXES: usize = 8;
+const AXIS_LEFT_X: usize = 0;
+const AXIS_LEFT_Y: usize = 1;
+const AXIS_DPAD_X: usize = 6;
+const AXIS_DPAD_Y: usize = 7;
+
+#[derive(Clone, Copy, Default)]
+struct PadState {
+    buttons: [bool; MAX_BUTTONS],
+    axes: [f32; MAX_AXES],
+}
+
+impl PadState {
+    // D-pad, or the left stick past the dead zone, as a unit step
+    fn direction(&self) -> (i32, i32) {
+        let step = |dpad: f32, stick: f32| {
+            let value = if dpad != 0.0 { dpad } else { stick };
+            if value > DEAD_ZONE {
+                1
+            } else if value < -DEAD_ZONE {
+                -1
+            } else {
+                0
+            }
+        };
+        (
+            step(self.axes[AXIS_DPAD_X], self.axes[AXIS_LEFT_X]),
+            step(self.axes[AXIS_DPAD_Y], self.axes[AXIS_LEFT_Y]),
+        )
+    }
+}
+
+struct Pad {
+    name: String,
+    device: backend::Device,
+    state: PadState,
+    previous: PadState,
+}
+
+struct Gamepads {
+    pads: Vec<Pad>,
+    last_scan: f64,
+}
+
+thread_local! {
+    static GAMEPADS: RefCell<Gamepads> = const { RefCell::new(Gamepads { pads: Vec::new(), last_scan: f64::NEG_INFINITY }) };
+}
+
+// Read pending gamepad events and look for newly connected ones. Call once per frame.
+pub fn update() {
+    GAMEPADS.with(|gamepads| {
+        let mut gamepads = gamepads.borrow_mut();
+
+        // Drop unplugged pads, read everything the others sent since last frame
+        gamepads.pads.retain_mut(|pad| {
+            pad.previous = pad.state;
+            pad.device.read(&mut pad.state)
+        });
+
+        if get_time() - gamepads.last_scan >= SCAN_INTERVAL {
+            gamepads.last_scan = get_time();
+            let known: Vec<String> = gamepads.pads.iter().map(|pad| pad.name.clone()).collect();
+            for (name, device) in backend::scan(&known) {
+                gamepads.pads.push(Pad {
+                    name,
+                    device,
+                    state: PadState::default(),
+                    previous: PadState::default(),
+                });
+            }
+        }
+    });
+}
+
+pub fn connected_count() -> usize {
+    GAMEPADS.with(|gamepads| gamepads.borrow().pads.len())
+}
+
+// Was the button pressed this frame on any gamepad?
+pub fn is_button_pressed(button: Button) -> bool {
+    let index = button.index();
+    GAMEPADS.with(|gamepads| {
+        gamepads
+            .borrow()
+            .pads
+            .iter()
+            .any(|pad| pad.state.buttons[index] && !pad.previous.buttons[index])
+    })
+}
+
+// D-pad or stick direction that was newly pushed this frame, for menu navigation
+pub fn direction_pressed() -> Option<(i32, i32)> {
+    GAMEPADS.with(|gamepads| {
+        gamepads.borrow().pads.iter().find_map(|pad| {
+            let now = pad.state.direction();
+            let before = pad.previous.direction();
+            let pressed = (
+                if now.0 != before.0 { now.0 } else { 0 },
+                if now.1 != before.1 { now.1 } else { 0 },
+            );
+            (pressed != (0, 0)).then_some(pressed)
+        })
+    })
+}
+
+// Left stick (or D-pad) position, each axis in -1..1 with the dead zone removed
+pub fn left_stick() -> Vec2 {
+    GAMEPADS.with(|gamepads| {
+        let mut stick = Vec2::ZERO;
+        for pad in &gamepads.borrow().pads {
+            let axes = &pad.state.axes;
+            let mut pad_stick = vec2(axes[AXIS_LEFT_X], axes[AXIS_LEFT_Y]);
+            if pad_stick.length() < DEAD_ZONE {
+                pad_stick = Vec2::ZERO;
+            }
+            if axes[AXIS_DPAD_X] != 0.0 || axes[AXIS_DPAD_Y] != 0.0 {
+                pad_stick = vec2(axes[AXIS_DPAD_X], axes[AXIS_DPAD_Y]);
+            }
+            stick += pad_stick;
+        }
+        stick.clamp(Vec2::splat(-1.0), Vec2::splat(1.0))
+    })
+}
+
+pub fn connected_names() -> Vec<String> {
+    GAMEPADS.with(|gamepads| gamepads.borrow().pads.iter().map(|pad| pad.name.clone()).collect())
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use std::fs::{File, OpenOptions};
+    use std::io::{ErrorKind, Read};
+    use std::os::unix::fs::OpenOptionsExt;
+
+    use super::{PadState, MAX_AXES, MAX_BUTTONS};
+
+    const MAX_DEVICES: usize = 8;
+    const EVENT_SIZE: usize = 8;
+    const EVENT_BUTTON: u8 = 0x01;
+    const EVENT_AXIS: u8 = 0x02;
+    const EVENT_INIT: u8 = 0x80;
+
+    pub struct Device {
+        file: File,
+    }
+
+    impl Device {
+        // Apply all pending events to `state`. Returns false once the device is gone.
+        pub fn read(&mut self, state: &mut PadState) -> bool {
+            let mut event = [0u8; EVENT_SIZE];
+            loop {
+                match self.file.read(&mut event) {
+                    Ok(EVENT_SIZE) => {
+                        let value = i16::from_le_bytes([event[4], event[5]]);
+                        let kind = event[6] & !EVENT_INIT;
+                        let number = event[7] as usize;
+                        if kind == EVENT_BUTTON && number < MAX_BUTTONS {
+                            state.buttons[number] = value != 0;
+                        } else if kind == EVENT_AXIS && number < MAX_AXES {
+                            state.axes[number] = value as f32 / i16::MAX as f32;
+                        }
+                    }
+                    Ok(_) => return false,
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => return true,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => return false,
+                }
+            }
+        }
+    }
+
+    // Open joystick devices that aren't in `known` yet
+    pub fn scan(known: &[String]) -> Vec<(String, Device)> {
+        let mut found = Vec::new();
+        for i in 0..MAX_DEVICES {
+            let path = format!("/dev/input/js{}", i);
+            if known.contains(&path) {
+                continue;
+            }
+            let file = OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path);
+            if let Ok(file) = file {
+                found.push((path, Device { file }));
+            }
+        }
+        found
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod backend {
+    use super::PadState;
+
+    pub struct Device;
+
+    impl Device {
+        pub fn read(&mut self, _state: &mut PadState) -> bool {
+            false
+        }
+    }
+
+    pub fn scan(_known: &[String]) -> Vec<(String, Device)> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+
+const HIGHSCORES_PATH: &str = "highscores.txt";
+const TABLE_SIZE: usize = 10;
+
+// One finished run: its score and how many seconds it lasted
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Run {
+    pub score: i32,
+    pub seconds: f64,
+}
+
+// Best runs per mode, read from `highscores.txt` as `mode = score seconds` lines.
+// Each mode keeps whatever is needed for its top-TABLE_SIZE tables by score, by longest
+// time and by shortest time.
+pub struct HighScores {
+    runs: Vec<(String, Run)>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let text = fs::read_to_string(HIGHSCORES_PATH).unwrap_or_default();
+        Self { runs: parse(&text) }
+    }
+
+    pub fn save(&self) {
+        let text: String = self
+            .runs
+            .iter()
+            .map(|(mode, run)| format!("{} = {} {:.2}\n", mode, run.score, run.seconds))
+            .collect();
+        if let Err(err) = fs::write(HIGHSCORES_PATH, text) {
+            eprintln!("Failed to save {}: {}", HIGHSCORES_PATH, err);
+        }
+    }
+
+    // Add a run and drop the ones that no longer make any table
+    pub fn record(&mut self, mode: &str, run: Run) {
+        self.runs.push((mode.to_string(), run));
+        let mut keep = self.best_scores(mode);
+        keep.extend(self.best_times(mode, false));
+        keep.extend(self.best_times(mode, true));
+        self.runs.retain(|(m, run)| m != mode || keep.contains(run));
+    }
+
+    // Make `run` the only one for `mode`, for modes with a single run like a daily's official one
+    pub fn replace(&mut self, mode: &str, run: Run) {
+        self.runs.retain(|(m, _)| m != mode);
+        self.runs.push((mode.to_string(), run));
+    }
+
+    fn runs(&self, mode: &str) -> Vec<Run> {
+        self.runs.iter().filter(|(m, _)| m == mode).map(|&(_, run)| run).collect()
+    }
+
+    // Top runs for `mode`, highest score first
+    pub fn best_scores(&self, mode: &str) -> Vec<Run> {
+        let mut runs = self.runs(mode);
+        runs.sort_by(|a, b| b.score.cmp(&a.score).then(b.seconds.total_cmp(&a.seconds)));
+        runs.truncate(TABLE_SIZE);
+        runs
+    }
+
+    // Top runs for `mode` by time, longest first unless `shortest`
+    pub fn best_times(&self, mode: &str, shortest: bool) -> Vec<Run> {
+        let mut runs = self.runs(mode);
+        runs.sort_by(|a, b| a.seconds.total_cmp(&b.seconds).then(b.score.cmp(&a.score)));
+        if !shortest {
+            runs.reverse();
+        }
+        runs.truncate(TABLE_SIZE);
+        runs
+    }
+}
+
+fn parse(text: &str) -> Vec<(String, Run)> {
+    let mut runs = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((mode, value)) = line.split_once('=') else { continue };
+        let mut fields = value.split_whitespace();
+        let score = fields.next().and_then(|field| field.parse().ok());
+        let seconds = fields.next().and_then(|field| field.parse().ok());
+        if let (Some(score), Some(seconds)) = (score, seconds) {
+            runs.push((mode.trim().to_string(), Run { score, seconds }));
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_keep_the_best_runs_of_each_kind() {
+        let mut highscores = HighScores { runs: parse("other = 1 1.0\nsurvival = oops\n") };
+        for i in 0..30 {
+            highscores.record("survival", Run { score: i, seconds: 100.0 - i as f64 });
+        }
+
+        let scores = highscores.best_scores("survival");
+        assert_eq!(scores.len(), TABLE_SIZE);
+        assert_eq!(scores[0], Run { score: 29, seconds: 71.0 });
+        assert_eq!(highscores.best_times("survival", false)[0], Run { score: 0, seconds: 100.0 });
+        assert_eq!(highscores.best_times("survival", true)[0], Run { score: 29, seconds: 71.0 });
+        assert_eq!(highscores.best_scores("other"), vec![Run { score: 1, seconds: 1.0 }]);
+        // Runs 10..20 are in no table: not the best scores, nor the longest or shortest
+        assert_eq!(highscores.runs("survival").len(), 2 * TABLE_SIZE);
+    }
+}
@@ -0,0 +1,174 @@
+use macroquad::prelude::*;
+
+use crate::assets::Assets;
+use crate::sprite::Sprite;
+
+// Seconds a mole takes to rise out of (or sink back into) its hole
+const RISE_TIME: f64 = 0.15;
+
+// Size of the generated hole textures, matching a 256px wide mole at scale 1.0
+const HOLE_TEXTURE_WIDTH: u16 = 256;
+const HOLE_TEXTURE_HEIGHT: u16 = 112;
+
+// How far below its center a mole's hole sits, as a fraction of the mole's height
+const GROUND_OFFSET: f32 = 0.36;
+
+// How far out of its hole a mole is: 0.0 hidden, 1.0 fully up.
+// `since_toggle` is the time since the mole last appeared or disappeared.
+pub fn rise(visible: bool, since_toggle: f64) -> f32 {
+    let progress = (since_toggle / RISE_TIME).clamp(0.0, 1.0) as f32;
+    if visible {
+        progress
+    } else {
+        1.0 - progress
+    }
+}
+
+// Screen y of the ground line a mole centered on `center` emerges from
+fn ground_y(sprite: &Sprite, center: Vec2, scale: f32) -> f32 {
+    center.y + sprite.texture.height() * scale * GROUND_OFFSET
+}
+
+// Where the mole currently is on screen, shifted down while it is still rising
+fn risen_rect(sprite: &Sprite, center: Vec2, scale: f32, rise: f32) -> Rect {
+    let mut rect = sprite.rect(center.x, center.y, scale);
+    rect.y += rect.h * (1.0 - rise);
+    rect
+}
+
+// Draw one hole back to front: the hole, the part of the mole above ground, then the front mound
+pub fn draw(assets: &Assets, sprite: &Sprite, center: Vec2, scale: f32, rise: f32) {
+    let ground = ground_y(sprite, center, scale);
+    let size = vec2(HOLE_TEXTURE_WIDTH as f32, HOLE_TEXTURE_HEIGHT as f32) * scale;
+    let hole_params = DrawTextureParams {
+        dest_size: Some(size),
+        ..Default::default()
+    };
+    let hole_x = center.x - size.x / 2.0;
+    let hole_y = ground - size.y / 2.0;
+
+    draw_texture_ex(&assets.hole, hole_x, hole_y, WHITE, hole_params.clone());
+    if rise > 0.0 {
+        let rect = risen_rect(sprite, center, scale, rise);
+        sprite.draw_top(rect, ground - rect.y);
+    }
+    draw_texture_ex(&assets.mound, hole_x, hole_y, WHITE, hole_params);
+}
+
+// Draw `text` on a badge on the mole's front, once it is far enough out of the hole.
+// The badge stretches to fit longer text, and its first `typed` characters are green.
+pub fn draw_badge(sprite: &Sprite, center: Vec2, scale: f32, rise: f32, text: &str, typed: usize) {
+    let rect = risen_rect(sprite, center, scale, rise);
+    let radius = rect.h * 0.12;
+    let badge = vec2(rect.x + rect.w / 2.0, rect.y + rect.h * 0.55);
+    if badge.y + radius > ground_y(sprite, center, scale) {
+        return;
+    }
+    let font_size = (radius * 1.2) as u16;
+    let size = measure_text(text, None, font_size, 1.0);
+
+    // A pill: two round ends joined by a rectangle, outlined by a slightly bigger black one
+    let half = (size.width / 2.0 + radius * 0.4 - radius).max(0.0);
+    for (grow, color) in [(3.0, BLACK), (0.0, WHITE)] {
+        draw_circle(badge.x - half, badge.y, radius + grow, color);
+        draw_circle(badge.x + half, badge.y, radius + grow, color);
+        draw_rectangle(badge.x - half, badge.y - radius - grow, half * 2.0, (radius + grow) * 2.0, color);
+    }
+
+    let split = text.char_indices().nth(typed).map_or(text.len(), |(i, _)| i);
+    let (done, rest) = text.split_at(split);
+    let left = badge.x - size.width / 2.0;
+    let baseline = badge.y + size.height / 2.0;
+    draw_text(done, left, baseline, font_size as f32, DARKGREEN);
+    let done_width = measure_text(done, None, font_size, 1.0).width;
+    draw_text(rest, left + done_width, baseline, font_size as f32, BLACK);
+}
+
+// Draw a label (e.g. the hole's key) centered just below the hole
+pub fn draw_label(sprite: &Sprite, center: Vec2, scale: f32, text: &str) {
+    let font_size = 30.0;
+    let below = ground_y(sprite, center, scale) + HOLE_TEXTURE_HEIGHT as f32 * scale / 2.0 + font_size;
+    let size = measure_text(text, None, font_size as u16, 1.0);
+    draw_text(text, center.x - size.width / 2.0, below, font_size, WHITE);
+}
+
+// Hit test against the part of the mole that is out of the ground
+pub fn contains(sprite: &Sprite, center: Vec2, scale: f32, rise: f32, point: Vec2, leniency: f32) -> bool {
+    if rise <= 0.0 || point.y > ground_y(sprite, center, scale) {
+        return false;
+    }
+    sprite.contains(risen_rect(sprite, center, scale, rise), point.x, point.y, leniency)
+}
+
+// Hit test against the hole and its dirt ring, whether or not a mole is up in it
+pub fn ring_contains(sprite: &Sprite, center: Vec2, scale: f32, point: Vec2, leniency: f32) -> bool {
+    in_ring(vec2(center.x, ground_y(sprite, center, scale)), scale, point, leniency)
+}
+
+// Whether `point` is within `leniency` pixels of a hole drawn at `scale` around `ground`
+fn in_ring(ground: Vec2, scale: f32, point: Vec2, leniency: f32) -> bool {
+    let radius_x = HOLE_TEXTURE_WIDTH as f32 * scale / 2.0 + leniency;
+    let radius_y = HOLE_TEXTURE_HEIGHT as f32 * scale / 2.0 + leniency;
+    let offset = point - ground;
+    (offset.x / radius_x).powi(2) + (offset.y / radius_y).powi(2) <= 1.0
+}
+
+// The dark opening surrounded by a ring of dirt
+pub fn hole_image() -> Image {
+    let dirt = Color::from_rgba(120, 82, 45, 255);
+    let opening = Color::from_rgba(35, 22, 12, 255);
+    paint(|in_ring, in_opening, _| {
+        if in_opening {
+            Some(opening)
+        } else if in_ring {
+            Some(dirt)
+        } else {
+            None
+        }
+    })
+}
+
+// The front half of the dirt ring, drawn over the mole so it appears to come out of the ground
+pub fn mound_image() -> Image {
+    let dirt = Color::from_rgba(138, 96, 54, 255);
+    paint(|in_ring, _, in_front| if in_ring && in_front { Some(dirt) } else { None })
+}
+
+// Fill a hole-sized image, asking `color_at(in_ring, in_opening, in_front)` for every pixel
+fn paint(color_at: impl Fn(bool, bool, bool) -> Option<Color>) -> Image {
+    let mut image = Image::gen_image_color(HOLE_TEXTURE_WIDTH, HOLE_TEXTURE_HEIGHT, BLANK);
+    let center_x = HOLE_TEXTURE_WIDTH as f32 / 2.0;
+    let center_y = HOLE_TEXTURE_HEIGHT as f32 / 2.0;
+    let (outer_rx, outer_ry) = (center_x - 2.0, center_y - 2.0);
+    let (inner_rx, inner_ry) = (outer_rx * 0.8, outer_ry * 0.62);
+
+    for py in 0..HOLE_TEXTURE_HEIGHT as u32 {
+        for px in 0..HOLE_TEXTURE_WIDTH as u32 {
+            let dx = px as f32 + 0.5 - center_x;
+            let dy = py as f32 + 0.5 - center_y;
+            let in_outer = (dx / outer_rx).powi(2) + (dy / outer_ry).powi(2) <= 1.0;
+            let in_opening = (dx / inner_rx).powi(2) + (dy / inner_ry).powi(2) <= 1.0;
+            if let Some(color) = color_at(in_outer && !in_opening, in_opening, dy >= 0.0) {
+                image.set_pixel(px, py, color);
+            }
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_ring_covers_the_drawn_hole() {
+        let ground = vec2(400.0, 300.0);
+        let (half_width, half_height) = (HOLE_TEXTURE_WIDTH as f32 * 0.5 / 2.0, HOLE_TEXTURE_HEIGHT as f32 * 0.5 / 2.0);
+        assert!(in_ring(ground, 0.5, ground, 0.0));
+        assert!(in_ring(ground, 0.5, ground + vec2(half_width - 1.0, 0.0), 0.0));
+        assert!(in_ring(ground, 0.5, ground + vec2(0.0, half_height - 1.0), 0.0));
+        // Above the hole, where a hidden mole's shape would be, doesn't count
+        assert!(!in_ring(ground, 0.5, ground - vec2(0.0, half_height + 10.0), 0.0));
+        assert!(in_ring(ground, 0.5, ground - vec2(0.0, half_height + 10.0), 12.0));
+    }
+}
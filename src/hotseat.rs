@@ -0,0 +1,254 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::{self, Game};
+use crate::gamepad::{self, Button};
+use crate::keyboard;
+use crate::menu::{draw_button, is_button_clicked};
+use crate::settings::Settings;
+
+const PLAYERS: usize = 2;
+const ROUNDS: usize = 2;
+const NAME_LENGTH: usize = 12;
+
+enum Phase {
+    Names { editing: usize }, // Typing in player `editing`'s name
+    Ready,                    // Waiting for the next player to take the seat
+    Playing(Box<Game>),
+    Scoreboard,
+}
+
+// Hot-seat competition: players take turns at Medium mode, every player gets the
+// same moles each round, and the scoreboard at the end compares them.
+pub struct HotSeat {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    names: [String; PLAYERS],
+    seeds: [u64; ROUNDS],          // Shared by every player in that round
+    scores: Vec<[i32; PLAYERS]>,   // One row per round started so far
+    turn: usize,                   // Turns played so far, round by round
+    phase: Phase,
+}
+
+impl HotSeat {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        // Drop anything typed before the prompt opened
+        clear_input_queue();
+        Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            names: Default::default(),
+            seeds: Self::new_seeds(),
+            scores: Vec::new(),
+            turn: 0,
+            phase: Phase::Names { editing: 0 },
+        }
+    }
+
+    fn new_seeds() -> [u64; ROUNDS] {
+        std::array::from_fn(|_| game2::random_seed())
+    }
+
+    fn round(&self) -> usize {
+        self.turn / PLAYERS
+    }
+
+    fn player(&self) -> usize {
+        self.turn % PLAYERS
+    }
+
+    fn name(&self, player: usize) -> String {
+        if self.names[player].trim().is_empty() {
+            format!("Player {}", player + 1)
+        } else {
+            self.names[player].trim().to_string()
+        }
+    }
+
+    fn total(&self, player: usize) -> i32 {
+        self.scores.iter().map(|round| round[player]).sum()
+    }
+
+    // Enter, a click or gamepad A moves on from a prompt
+    fn confirm_pressed() -> bool {
+        is_key_pressed(KeyCode::Enter)
+            || is_key_pressed(KeyCode::KpEnter)
+            || is_mouse_button_pressed(MouseButton::Left)
+            || gamepad::is_button_pressed(Button::A)
+    }
+
+    // Returns true when the players want to go back to the menu
+    pub fn update(&mut self) -> bool {
+        match &mut self.phase {
+            Phase::Names { editing } => {
+                let mut editing = *editing;
+                let mut start = false;
+                // Backspace edits the name here, so only the button and gamepad B leave
+                keyboard::edit_text(&mut self.names[editing], NAME_LENGTH);
+                if is_key_pressed(KeyCode::Tab) {
+                    editing = (editing + 1) % PLAYERS;
+                }
+                if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A) {
+                    if editing + 1 < PLAYERS {
+                        editing += 1;
+                    } else {
+                        start = true;
+                    }
+                }
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    let (mouse_x, mouse_y) = mouse_position();
+                    if let Some(player) = (0..PLAYERS).find(|&p| Self::name_rect(p).contains(vec2(mouse_x, mouse_y))) {
+                        editing = player;
+                    } else if is_button_clicked(60.0, 460.0, 200.0, 60.0, mouse_x, mouse_y) {
+                        return true;
+                    } else if is_button_clicked(280.0, 460.0, 200.0, 60.0, mouse_x, mouse_y) {
+                        start = true;
+                    }
+                }
+                self.phase = if start { Phase::Ready } else { Phase::Names { editing } };
+                gamepad::is_button_pressed(Button::B)
+            }
+
+            Phase::Ready => {
+                if Self::confirm_pressed() {
+                    let game = Game::with_seed(2, self.assets.clone(), &self.settings, &self.bindings, self.seeds[self.round()])
+                        .without_restart();
+                    self.phase = Phase::Playing(Box::new(game));
+                }
+                self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::B)
+            }
+
+            Phase::Playing(game) => {
+                // Leaving mid-round abandons the whole competition, there are no restarts
+                if game.update() {
+                    return true;
+                }
+                let finished = game.is_over()
+                    && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A));
+                if finished {
+                    let score = game.score();
+                    if self.player() == 0 {
+                        self.scores.push([0; PLAYERS]);
+                    }
+                    let (round, player) = (self.round(), self.player());
+                    self.scores[round][player] = score;
+                    self.turn += 1;
+                    self.phase = if self.turn == ROUNDS * PLAYERS { Phase::Scoreboard } else { Phase::Ready };
+                }
+                false
+            }
+
+            Phase::Scoreboard => {
+                // Rematch: same players, new moles
+                if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A) {
+                    self.seeds = Self::new_seeds();
+                    self.scores.clear();
+                    self.turn = 0;
+                    self.phase = Phase::Ready;
+                }
+                self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::B)
+            }
+        }
+    }
+
+    fn name_rect(player: usize) -> Rect {
+        Rect::new(300.0, 180.0 + player as f32 * 80.0, 400.0, 60.0)
+    }
+
+    pub fn draw(&self) {
+        match &self.phase {
+            Phase::Names { editing } => {
+                draw_text("HOT-SEAT", 60.0, 80.0, 60.0, BLACK);
+                draw_text("Type each player's name, Enter for the next one (Tab or click to switch).", 60.0, 130.0, 24.0, DARKGRAY);
+                for player in 0..PLAYERS {
+                    let rect = Self::name_rect(player);
+                    draw_text(&format!("Player {}:", player + 1), 60.0, rect.y + 42.0, 40.0, BLACK);
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, WHITE);
+                    let outline = if player == *editing { GOLD } else { BLACK };
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 3.0, outline);
+                    // Blinking caret on the name being typed
+                    let caret = if player == *editing && get_time().fract() < 0.5 { "|" } else { "" };
+                    draw_text(&format!("{}{}", self.names[player], caret), rect.x + 12.0, rect.y + 42.0, 40.0, BLACK);
+                }
+                draw_text(
+                    &format!("{} rounds each, everyone gets the same moles in a round.", ROUNDS),
+                    60.0,
+                    400.0,
+                    28.0,
+                    DARKGRAY,
+                );
+                draw_button(60.0, 460.0, 200.0, 60.0, "Back", GRAY);
+                draw_button(280.0, 460.0, 200.0, 60.0, "Start", GREEN);
+            }
+
+            Phase::Ready => {
+                draw_text(&format!("Round {} of {}", self.round() + 1, ROUNDS), 60.0, 120.0, 50.0, DARKGRAY);
+                draw_text(&format!("{}, take the seat!", self.name(self.player())), 60.0, 220.0, 60.0, BLACK);
+                draw_text("Press Enter, click or press A when ready.", 60.0, 300.0, 30.0, DARKGRAY);
+                self.draw_scores(400.0);
+            }
+
+            Phase::Playing(game) => {
+                game.draw();
+                draw_text(
+                    &format!("{} - round {} of {}", self.name(self.player()), self.round() + 1, ROUNDS),
+                    20.0,
+                    120.0,
+                    30.0,
+                    WHITE,
+                );
+                if game.is_over() {
+                    draw_text("Press Enter or A to hand over.", 20.0, screen_height() - 110.0, 30.0, RED);
+                }
+            }
+
+            Phase::Scoreboard => {
+                draw_text("FINAL SCORES", 60.0, 80.0, 60.0, BLACK);
+                let best = (0..PLAYERS).map(|p| self.total(p)).max().unwrap_or(0);
+                let winners: Vec<String> = (0..PLAYERS).filter(|&p| self.total(p) == best).map(|p| self.name(p)).collect();
+                let result = if winners.len() == 1 {
+                    format!("{} wins!", winners[0])
+                } else {
+                    format!("It's a tie: {}", winners.join(" and "))
+                };
+                draw_text(&result, 60.0, 160.0, 50.0, DARKGREEN);
+                self.draw_scores(240.0);
+                draw_text(
+                    &format!(
+                        "Press Enter or A for a rematch, {} to return to menu.",
+                        self.bindings.label(Action::Back),
+                    ),
+                    60.0,
+                    screen_height() - 40.0,
+                    25.0,
+                    DARKGRAY,
+                );
+            }
+        }
+    }
+
+    // Table of every finished round, one row per player
+    fn draw_scores(&self, top: f32) {
+        if self.turn == 0 {
+            return;
+        }
+        for round in 0..ROUNDS {
+            draw_text(&format!("R{}", round + 1), 360.0 + round as f32 * 100.0, top, 30.0, DARKGRAY);
+        }
+        draw_text("Total", 360.0 + ROUNDS as f32 * 100.0, top, 30.0, DARKGRAY);
+        for player in 0..PLAYERS {
+            let y = top + 40.0 * (player + 1) as f32;
+            draw_text(&self.name(player), 60.0, y, 30.0, BLACK);
+            for round in 0..ROUNDS {
+                let played = round * PLAYERS + player < self.turn;
+                let text = if played { self.scores[round][player].to_string() } else { "-".to_string() };
+                draw_text(&text, 360.0 + round as f32 * 100.0, y, 30.0, BLACK);
+            }
+            draw_text(&self.total(player).to_string(), 360.0 + ROUNDS as f32 * 100.0, y, 30.0, BLACK);
+        }
+    }
+}
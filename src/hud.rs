@@ -0,0 +1,151 @@
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+use crate::bindings::{Action, Bindings};
+
+// Floating popups rise this many pixels over their lifetime
+const POPUP_LIFETIME: f64 = 0.8;
+const POPUP_DRIFT: f32 = 60.0;
+const POPUP_FONT_SIZE: f32 = 40.0;
+
+// HUD notices stay up for a while, newest at the bottom, and fade out at the end
+const NOTICE_LIFETIME: f64 = 2.5;
+const NOTICE_FADE: f64 = 0.5;
+const MAX_NOTICES: usize = 3;
+const NOTICE_FONT_SIZE: f32 = 30.0;
+
+// Gamepad cursor speed in pixels per second at full stick
+const CURSOR_SPEED: f32 = 900.0;
+
+struct Popup {
+    text: String,
+    x: f32,
+    y: f32,
+    color: Color,
+    spawned: f64,
+}
+
+// Short score texts ("+1", "Clank!") that drift up from where a hit landed and fade out
+pub struct Popups {
+    popups: Vec<Popup>,
+}
+
+impl Popups {
+    pub fn new() -> Self {
+        Self { popups: Vec::new() }
+    }
+
+    pub fn spawn(&mut self, text: &str, x: f32, y: f32, color: Color) {
+        self.popups.push(Popup {
+            text: text.to_string(),
+            x,
+            y,
+            color,
+            spawned: get_time(),
+        });
+    }
+
+    pub fn update(&mut self) {
+        let now = get_time();
+        self.popups.retain(|popup| now - popup.spawned < POPUP_LIFETIME);
+    }
+
+    pub fn draw(&self) {
+        let now = get_time();
+        for popup in &self.popups {
+            let age = ((now - popup.spawned) / POPUP_LIFETIME).clamp(0.0, 1.0) as f32;
+            let size = measure_text(&popup.text, None, POPUP_FONT_SIZE as u16, 1.0);
+            let mut color = popup.color;
+            color.a *= 1.0 - age;
+            draw_text(
+                &popup.text,
+                popup.x - size.width / 2.0,
+                popup.y - POPUP_DRIFT * age,
+                POPUP_FONT_SIZE,
+                color,
+            );
+        }
+    }
+}
+
+struct Notice {
+    text: String,
+    color: Color,
+    posted: f64,
+}
+
+// A small queue of HUD messages, so a new one doesn't wipe out the one before it
+pub struct Notices {
+    notices: VecDeque<Notice>,
+}
+
+impl Notices {
+    pub fn new() -> Self {
+        Self { notices: VecDeque::new() }
+    }
+
+    pub fn push(&mut self, text: &str, color: Color) {
+        // Repeating the newest notice just keeps it up longer
+        if let Some(last) = self.notices.back_mut() {
+            if last.text == text {
+                last.posted = get_time();
+                return;
+            }
+        }
+        self.notices.push_back(Notice {
+            text: text.to_string(),
+            color,
+            posted: get_time(),
+        });
+        while self.notices.len() > MAX_NOTICES {
+            self.notices.pop_front();
+        }
+    }
+
+    pub fn update(&mut self) {
+        let now = get_time();
+        self.notices.retain(|notice| now - notice.posted < NOTICE_LIFETIME);
+    }
+
+    // Draw the notices stacked upwards from `bottom`
+    pub fn draw(&self, x: f32, bottom: f32) {
+        let now = get_time();
+        for (i, notice) in self.notices.iter().rev().enumerate() {
+            let remaining = NOTICE_LIFETIME - (now - notice.posted);
+            let mut color = notice.color;
+            color.a *= (remaining / NOTICE_FADE).clamp(0.0, 1.0) as f32;
+            draw_text(&notice.text, x, bottom - i as f32 * NOTICE_FONT_SIZE, NOTICE_FONT_SIZE, color);
+        }
+    }
+}
+
+// Move a stick-driven cursor, keeping it on screen
+pub fn move_cursor(cursor: Vec2, stick: Vec2) -> Vec2 {
+    let moved = cursor + stick * CURSOR_SPEED * get_frame_time();
+    moved.clamp(Vec2::ZERO, vec2(screen_width(), screen_height()))
+}
+
+// A hammer cursor with its striking point at `tip`, for gamepad play
+pub fn draw_hammer(tip: Vec2, color: Color) {
+    draw_line(tip.x + 18.0, tip.y - 4.0, tip.x + 52.0, tip.y + 40.0, 8.0, BROWN);
+    draw_rectangle(tip.x - 4.0, tip.y - 26.0, 44.0, 26.0, color);
+    draw_rectangle_lines(tip.x - 4.0, tip.y - 26.0, 44.0, 26.0, 2.0, BLACK);
+    draw_circle_lines(tip.x, tip.y, 6.0, 2.0, color);
+}
+
+// Dim the round and list what can be done from the pause screen, restarting only
+// where the mode supports it
+pub fn draw_pause_overlay(bindings: &Bindings, restart: bool) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+    let title = "PAUSED";
+    let size = measure_text(title, None, 80, 1.0);
+    draw_text(title, (screen_width() - size.width) / 2.0, screen_height() / 2.0 - 40.0, 80.0, WHITE);
+
+    let mut hint = format!("{}: resume   ", bindings.label(Action::Pause));
+    if restart {
+        hint += &format!("{}: restart   ", bindings.label(Action::Restart));
+    }
+    hint += &format!("{}: menu   {}: quit", bindings.label(Action::Back), bindings.label(Action::Quit));
+    let size = measure_text(&hint, None, 30, 1.0);
+    draw_text(&hint, (screen_width() - size.width) / 2.0, screen_height() / 2.0 + 20.0, 30.0, LIGHTGRAY);
+}
@@ -0,0 +1,154 @@
+use macroquad::input::utils::{register_input_subscriber, repeat_all_miniquad_input};
+use macroquad::miniquad::{self, EventHandler};
+use macroquad::prelude::*;
+use std::cell::RefCell;
+
+use crate::bindings::{Action, Bindings, Input};
+
+// Pointer input as a queue of press events, so clicks and taps that arrive in the same
+// frame (or during a hitch) each become their own hit attempt instead of collapsing
+// into one `is_mouse_button_pressed`. Call `update()` once per frame, then read `presses()`.
+// Hit and Pet follow the bindings; when bound to a key they act at the mouse position.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PressKind {
+    Primary,   // Hit (left click) or touch: whack
+    Secondary, // Pet (right click)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Press {
+    pub kind: PressKind,
+    pub x: f32,
+    pub y: f32,
+}
+
+// Receives the raw miniquad events replayed by macroquad, in the order they happened
+struct Collector {
+    hit: Input,
+    pet: Input,
+    presses: Vec<Press>,
+    last_from_mouse: bool, // Whether the newest press came from a mouse event
+}
+
+impl Collector {
+    fn new(hit: Input, pet: Input) -> Self {
+        Self {
+            hit,
+            pet,
+            presses: Vec::new(),
+            last_from_mouse: false,
+        }
+    }
+
+    fn push(&mut self, kind: PressKind, x: f32, y: f32, from_mouse: bool) {
+        self.presses.push(Press { kind, x, y });
+        self.last_from_mouse = from_mouse;
+    }
+}
+
+impl EventHandler for Collector {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        if Input::Mouse(button) == self.hit {
+            self.push(PressKind::Primary, x, y, true);
+        } else if Input::Mouse(button) == self.pet {
+            self.push(PressKind::Secondary, x, y, true);
+        }
+    }
+
+    fn touch_event(&mut self, phase: miniquad::TouchPhase, _id: u64, x: f32, y: f32) {
+        if phase != miniquad::TouchPhase::Started {
+            return;
+        }
+        // macroquad reports a new touch as a left click right before the touch itself
+        if let Some(last) = self.presses.last() {
+            if self.last_from_mouse && last.kind == PressKind::Primary && last.x == x && last.y == y {
+                self.presses.pop();
+            }
+        }
+        self.push(PressKind::Primary, x, y, false);
+    }
+}
+
+struct InputQueue {
+    subscriber: usize,
+    collector: Collector,
+}
+
+thread_local! {
+    static QUEUE: RefCell<Option<InputQueue>> = const { RefCell::new(None) };
+}
+
+// Collect every press since the last frame. Call once per frame.
+pub fn update(bindings: &Bindings) {
+    QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        let queue = queue.get_or_insert_with(|| InputQueue {
+            subscriber: register_input_subscriber(),
+            collector: Collector::new(bindings.get(Action::Hit), bindings.get(Action::Pet)),
+        });
+        let collector = &mut queue.collector;
+        collector.hit = bindings.get(Action::Hit);
+        collector.pet = bindings.get(Action::Pet);
+        collector.presses.clear();
+        repeat_all_miniquad_input(collector, queue.subscriber);
+
+        // Hit and Pet bound to keys strike wherever the mouse is
+        let (mouse_x, mouse_y) = mouse_position();
+        if let Input::Key(key) = collector.hit {
+            if is_key_pressed(key) {
+                collector.push(PressKind::Primary, mouse_x, mouse_y, false);
+            }
+        }
+        if let Input::Key(key) = collector.pet {
+            if is_key_pressed(key) {
+                collector.push(PressKind::Secondary, mouse_x, mouse_y, false);
+            }
+        }
+    });
+}
+
+// This frame's presses, oldest first
+pub fn presses() -> Vec<Press> {
+    QUEUE.with(|queue| {
+        queue
+            .borrow()
+            .as_ref()
+            .map(|queue| queue.collector.presses.clone())
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_click_in_one_tick_is_a_hit_attempt() {
+        let mut collector = Collector::new(Input::Mouse(MouseButton::Left), Input::Mouse(MouseButton::Right));
+        for i in 0..5 {
+            collector.mouse_button_down_event(MouseButton::Left, i as f32 * 100.0, 50.0);
+        }
+
+        assert_eq!(collector.presses.len(), 5);
+        for (i, press) in collector.presses.iter().enumerate() {
+            assert_eq!(press.kind, PressKind::Primary);
+            assert_eq!(press.x, i as f32 * 100.0);
+        }
+    }
+
+    #[test]
+    fn a_touch_replaces_its_simulated_click() {
+        let mut collector = Collector::new(Input::Mouse(MouseButton::Left), Input::Mouse(MouseButton::Right));
+        collector.mouse_button_down_event(MouseButton::Left, 10.0, 20.0);
+        collector.touch_event(miniquad::TouchPhase::Started, 1, 10.0, 20.0);
+        collector.mouse_button_down_event(MouseButton::Left, 300.0, 20.0);
+        collector.touch_event(miniquad::TouchPhase::Started, 2, 300.0, 20.0);
+
+        assert_eq!(collector.presses.len(), 2);
+    }
+}
@@ -0,0 +1,51 @@
+use macroquad::prelude::*;
+
+use crate::bindings::{Action, Bindings, Input};
+
+// The input bound to each hole of a cols x rows grid, in the same order as `layout::grid`.
+// The 2x2 grid has its own keys, grids up to 3x3 use the top left of the 3x3 keys,
+// bigger grids have no keyboard layout.
+pub fn hole_inputs(cols: usize, rows: usize, bindings: &Bindings) -> Vec<Input> {
+    if cols == 2 && rows == 2 {
+        return (0..4).map(|i| bindings.get(Action::Hole2x2(i))).collect();
+    }
+    if cols > 3 || rows > 3 {
+        return Vec::new();
+    }
+    (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| row * 3 + col))
+        .map(|i| bindings.get(Action::Hole3x3(i)))
+        .collect()
+}
+
+// Index of the first hole whose input was pressed this frame
+pub fn pressed_hole(inputs: &[Input]) -> Option<usize> {
+    inputs.iter().position(|input| input.is_pressed())
+}
+
+// Text drawn under a hole for its key
+pub fn key_label(input: Input) -> String {
+    let name = input.name();
+    match name.strip_prefix("Kp") {
+        Some(digit) => format!("Num {}", digit),
+        None => name,
+    }
+}
+
+// Apply this frame's typing to `text`: printable characters are added up to `max_len`,
+// Backspace removes the last one
+pub fn edit_text(text: &mut String, max_len: usize) {
+    // macroquad hands out queued characters newest first
+    let mut typed = Vec::new();
+    while let Some(c) = get_char_pressed() {
+        typed.push(c);
+    }
+    for c in typed.into_iter().rev() {
+        if !c.is_control() && text.chars().count() < max_len {
+            text.push(c);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        text.pop();
+    }
+}
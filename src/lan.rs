@@ -0,0 +1,413 @@
+use macroquad::prelude::*;
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::{self, Game};
+use crate::gamepad::{self, Button};
+use crate::keyboard;
+use crate::menu::{draw_button, is_button_clicked};
+use crate::net::{self, Client, Host, HostEvent, Message, Standing};
+use crate::settings::Settings;
+
+const ROUND_TIME: f64 = 60.0;
+const FIELD_LENGTH: usize = 24;
+const HOST_ID: usize = 0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Address,
+}
+
+enum Role {
+    Host(Host),
+    Client { client: Client, id: Option<usize> },
+}
+
+enum Phase {
+    Setup,
+    Connecting(Receiver<io::Result<Client>>),
+    Lobby,
+    Playing(Box<Game>),
+}
+
+// LAN versus over TCP. One player hosts, the others join by address; the host starts
+// every round with a seed so all boards get the same moles, players stream their score
+// changes to the host and the host sends the scoreboard back out to everyone.
+pub struct Lan {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    name: String,
+    address: String,
+    editing: Field,
+    error: Option<String>,
+    role: Option<Role>,
+    phase: Phase,
+    standings: Vec<Standing>,
+    last_score: i32,   // Own score last reported, to send only the changes
+    reported_done: bool,
+    host_address: String, // Hosting: where the others join, looked up once on hosting
+}
+
+// Setup screen buttons: Host, Join, Back
+const SETUP_BUTTONS: [(f32, &str); 3] = [(60.0, "Host"), (280.0, "Join"), (500.0, "Back")];
+const SETUP_BUTTONS_Y: f32 = 400.0;
+
+impl Lan {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        // Drop anything typed before the screen opened
+        clear_input_queue();
+        Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            name: String::new(),
+            address: format!("127.0.0.1:{}", net::DEFAULT_PORT),
+            editing: Field::Name,
+            error: None,
+            role: None,
+            phase: Phase::Setup,
+            standings: Vec::new(),
+            last_score: 0,
+            reported_done: false,
+            host_address: String::new(),
+        }
+    }
+
+    fn player_name(&self) -> String {
+        if self.name.trim().is_empty() {
+            "Player".to_string()
+        } else {
+            self.name.trim().to_string()
+        }
+    }
+
+    fn field_rect(field: Field) -> Rect {
+        match field {
+            Field::Name => Rect::new(320.0, 160.0, 500.0, 60.0),
+            Field::Address => Rect::new(320.0, 260.0, 500.0, 60.0),
+        }
+    }
+
+    fn host(&mut self) {
+        match Host::start(net::DEFAULT_PORT) {
+            Ok(host) => {
+                self.standings = vec![Standing { id: HOST_ID, name: self.player_name(), score: 0, done: false }];
+                let address = net::local_address().map_or("this machine's address".to_string(), |ip| ip.to_string());
+                self.host_address = format!("{}:{}", address, host.port());
+                self.role = Some(Role::Host(host));
+                self.phase = Phase::Lobby;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(format!("Could not host on port {}: {}", net::DEFAULT_PORT, err)),
+        }
+    }
+
+    fn join(&mut self) {
+        // Connecting can take a few seconds, don't freeze the window meanwhile
+        let (sender, receiver) = mpsc::channel();
+        let address = self.address.trim().to_string();
+        thread::spawn(move || {
+            let _ = sender.send(Client::connect(&address));
+        });
+        self.phase = Phase::Connecting(receiver);
+        self.error = None;
+    }
+
+    // Back to the setup screen, closing any connection
+    fn disconnect(&mut self, error: Option<String>) {
+        self.role = None;
+        self.phase = Phase::Setup;
+        self.standings.clear();
+        self.error = error;
+    }
+
+    fn start_game(&mut self, seed: u64, duration: f64, frenzy: f32) {
+        let game = Game::with_seed(2, self.assets.clone(), &self.settings, &self.bindings, seed)
+            .with_duration(duration)
+            .with_frenzy(frenzy)
+            .without_restart();
+        self.phase = Phase::Playing(Box::new(game));
+        self.last_score = 0;
+        self.reported_done = false;
+        for standing in &mut self.standings {
+            standing.score = 0;
+            standing.done = false;
+        }
+    }
+
+    // Host only: start a round for everyone, with the host's frenzy length so the rules match
+    fn start_round(&mut self) {
+        let seed = game2::random_seed();
+        let frenzy = self.settings.frenzy_seconds;
+        if let Some(Role::Host(host)) = &self.role {
+            host.broadcast(&Message::Start { seed, duration: ROUND_TIME, frenzy });
+        }
+        self.start_game(seed, ROUND_TIME, frenzy);
+        self.broadcast_board();
+    }
+
+    fn broadcast_board(&self) {
+        if let Some(Role::Host(host)) = &self.role {
+            host.broadcast(&Message::Board(self.standings.clone()));
+        }
+    }
+
+    // Own score changes: the host updates its board, players tell the host
+    fn report(&mut self, score: i32, done: bool) {
+        let delta = score - self.last_score;
+        let finished = done && !self.reported_done;
+        if delta == 0 && !finished {
+            return;
+        }
+        self.last_score = score;
+        self.reported_done |= finished;
+        match &self.role {
+            Some(Role::Host(_)) => {
+                if let Some(own) = self.standings.iter_mut().find(|s| s.id == HOST_ID) {
+                    own.score = score;
+                    own.done = done;
+                }
+                self.broadcast_board();
+            }
+            Some(Role::Client { client, .. }) => {
+                if delta != 0 {
+                    client.send(&Message::Hit { delta, score });
+                }
+                if finished {
+                    client.send(&Message::Done { score });
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn poll_network(&mut self) {
+        match &self.role {
+            Some(Role::Host(host)) => {
+                let mut changed = false;
+                for event in host.poll() {
+                    changed = true;
+                    match event {
+                        HostEvent::Joined(id) => {
+                            self.standings.push(Standing { id, name: format!("Player {}", id), score: 0, done: false });
+                            host.send(id, &Message::Welcome { id });
+                        }
+                        HostEvent::Message(id, message) => {
+                            let Some(standing) = self.standings.iter_mut().find(|s| s.id == id) else { continue };
+                            match message {
+                                Message::Hello { name } => {
+                                    if let Some(name) = net::clean_name(&name) {
+                                        standing.name = name;
+                                    }
+                                }
+                                Message::Hit { score, .. } => standing.score = score,
+                                Message::Done { score } => {
+                                    standing.score = score;
+                                    standing.done = true;
+                                }
+                                _ => {}
+                            }
+                        }
+                        HostEvent::Left(id) => self.standings.retain(|s| s.id != id),
+                    }
+                }
+                if changed {
+                    self.broadcast_board();
+                }
+            }
+            Some(Role::Client { client, .. }) => {
+                let name = self.player_name();
+                for message in client.poll() {
+                    match message {
+                        Some(Message::Welcome { id }) => {
+                            if let Some(Role::Client { client, id: own }) = &mut self.role {
+                                client.send(&Message::Hello { name: name.clone() });
+                                *own = Some(id);
+                            }
+                        }
+                        Some(Message::Start { seed, duration, frenzy }) => self.start_game(seed, duration, frenzy),
+                        Some(Message::Board(standings)) => self.standings = standings,
+                        Some(_) => {}
+                        None => {
+                            self.disconnect(Some("The host closed the game.".to_string()));
+                            return;
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        self.poll_network();
+
+        match &mut self.phase {
+            Phase::Setup => {
+                // Backspace edits the fields here, so only the button and gamepad B leave
+                let text = match self.editing {
+                    Field::Name => &mut self.name,
+                    Field::Address => &mut self.address,
+                };
+                keyboard::edit_text(text, FIELD_LENGTH);
+                if is_key_pressed(KeyCode::Tab) {
+                    self.editing = if self.editing == Field::Name { Field::Address } else { Field::Name };
+                }
+                if is_mouse_button_pressed(MouseButton::Left) {
+                    let (mouse_x, mouse_y) = mouse_position();
+                    for field in [Field::Name, Field::Address] {
+                        if Self::field_rect(field).contains(vec2(mouse_x, mouse_y)) {
+                            self.editing = field;
+                        }
+                    }
+                    let clicked = SETUP_BUTTONS
+                        .iter()
+                        .position(|&(x, _)| is_button_clicked(x, SETUP_BUTTONS_Y, 200.0, 60.0, mouse_x, mouse_y));
+                    match clicked {
+                        Some(0) => self.host(),
+                        Some(1) => self.join(),
+                        Some(_) => return true,
+                        None => {}
+                    }
+                }
+                gamepad::is_button_pressed(Button::B)
+            }
+
+            Phase::Connecting(receiver) => {
+                match receiver.try_recv() {
+                    Ok(Ok(client)) => {
+                        self.role = Some(Role::Client { client, id: None });
+                        self.phase = Phase::Lobby;
+                    }
+                    Ok(Err(err)) => self.disconnect(Some(format!("Could not join {}: {}", self.address.trim(), err))),
+                    Err(_) => {}
+                }
+                false
+            }
+
+            Phase::Lobby => {
+                let is_host = matches!(self.role, Some(Role::Host(_)));
+                let start = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A);
+                if is_host && start {
+                    self.start_round();
+                    return false;
+                }
+                if self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::B) {
+                    self.disconnect(None);
+                }
+                false
+            }
+
+            Phase::Playing(game) => {
+                if game.update() {
+                    // Leaving a round leaves the LAN game too
+                    self.disconnect(None);
+                    return false;
+                }
+                let (score, done) = (game.score(), game.is_over());
+                self.report(score, done);
+
+                // After the round: the host starts the next one, anyone can leave
+                if done {
+                    let is_host = matches!(self.role, Some(Role::Host(_)));
+                    let next = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A);
+                    if is_host && next {
+                        self.start_round();
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    // Scoreboard, best first, with players still playing marked during a round
+    fn draw_standings(&self, x: f32, top: f32, color: Color, in_round: bool) {
+        let mut standings = self.standings.clone();
+        standings.sort_by_key(|standing| std::cmp::Reverse(standing.score));
+        let own_id = match &self.role {
+            Some(Role::Host(_)) => Some(HOST_ID),
+            Some(Role::Client { id, .. }) => *id,
+            None => None,
+        };
+        for (i, standing) in standings.iter().enumerate() {
+            let status = if in_round && !standing.done { " ..." } else { "" };
+            let marker = if Some(standing.id) == own_id { "> " } else { "  " };
+            draw_text(
+                &format!("{}{}. {}: {}{}", marker, i + 1, standing.name, standing.score, status),
+                x,
+                top + i as f32 * 32.0,
+                30.0,
+                color,
+            );
+        }
+    }
+
+    pub fn draw(&self) {
+        match &self.phase {
+            Phase::Setup | Phase::Connecting(_) => {
+                draw_text("LAN GAME", 60.0, 80.0, 60.0, BLACK);
+                draw_text("Host a game, or type the host's address and join. Tab switches fields.", 60.0, 125.0, 24.0, DARKGRAY);
+                for (field, label) in [(Field::Name, "Your name:"), (Field::Address, "Host address:")] {
+                    let rect = Self::field_rect(field);
+                    draw_text(label, 60.0, rect.y + 42.0, 36.0, BLACK);
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, WHITE);
+                    let outline = if field == self.editing { GOLD } else { BLACK };
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 3.0, outline);
+                    let text = if field == Field::Name { &self.name } else { &self.address };
+                    let caret = if field == self.editing && get_time().fract() < 0.5 { "|" } else { "" };
+                    draw_text(&format!("{}{}", text, caret), rect.x + 12.0, rect.y + 42.0, 36.0, BLACK);
+                }
+                for (x, label) in SETUP_BUTTONS {
+                    draw_button(x, SETUP_BUTTONS_Y, 200.0, 60.0, label, GRAY);
+                }
+                if let Phase::Connecting(_) = self.phase {
+                    draw_text(&format!("Connecting to {}...", self.address.trim()), 60.0, 520.0, 30.0, DARKGRAY);
+                }
+                if let Some(error) = &self.error {
+                    draw_text(error, 60.0, 520.0, 30.0, RED);
+                }
+            }
+
+            Phase::Lobby => {
+                draw_text("LOBBY", 60.0, 80.0, 60.0, BLACK);
+                let info = match &self.role {
+                    Some(Role::Host(_)) => {
+                        format!("Hosting: other players join {}. Press Enter or A to start.", self.host_address)
+                    }
+                    _ => "Joined, waiting for the host to start the round.".to_string(),
+                };
+                draw_text(&info, 60.0, 130.0, 28.0, DARKGRAY);
+                self.draw_standings(60.0, 200.0, BLACK, false);
+                draw_text(
+                    &format!("Press {} to leave.", self.bindings.label(Action::Back)),
+                    60.0,
+                    screen_height() - 40.0,
+                    25.0,
+                    DARKGRAY,
+                );
+            }
+
+            Phase::Playing(game) => {
+                game.draw();
+                let left = screen_width() - 420.0;
+                let height = 50.0 + self.standings.len() as f32 * 32.0;
+                draw_rectangle(left - 20.0, 10.0, 430.0, height, Color::new(0.0, 0.0, 0.0, 0.5));
+                self.draw_standings(left, 45.0, WHITE, true);
+                if game.is_over() {
+                    let hint = match self.role {
+                        Some(Role::Host(_)) => "Press Enter or A to start the next round.",
+                        _ => "Waiting for the host to start the next round.",
+                    };
+                    draw_text(hint, 20.0, screen_height() - 110.0, 30.0, RED);
+                }
+            }
+        }
+    }
+}
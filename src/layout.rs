@@ -0,0 +1,20 @@
+// Centre of the hole grid on the background, and the gap between holes at scale 1.0
+const GRID_CENTER: (f32, f32) = (650.0, 350.0);
+const HOLE_SPACING: (f32, f32) = (400.0, 300.0);
+
+// Hole positions for a cols x rows grid, row by row from the top left.
+// Spacing follows the sprite scale so smaller moles sit closer together.
+pub fn grid(cols: usize, rows: usize, scale: f32) -> Vec<(f32, f32)> {
+    let spacing_x = HOLE_SPACING.0 * scale;
+    let spacing_y = HOLE_SPACING.1 * scale;
+    let left = GRID_CENTER.0 - spacing_x * (cols as f32 - 1.0) / 2.0;
+    let top = GRID_CENTER.1 - spacing_y * (rows as f32 - 1.0) / 2.0;
+
+    let mut positions = Vec::with_capacity(cols * rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            positions.push((left + spacing_x * col as f32, top + spacing_y * row as f32));
+        }
+    }
+    positions
+}
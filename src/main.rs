@@ -1,66 +1,221 @@
 use macroquad::prelude::*;
 use std::sync::Arc;
 
-mod menu;
+mod assets;
+mod asymmetric;
+mod bindings;
+mod campaign;
+mod clock;
+mod controls;
+mod daily;
 mod game1;
 mod game2;
+mod gamepad;
+mod highscores;
+mod hole;
+mod hotseat;
+mod hud;
+mod input;
+mod keyboard;
+mod lan;
+mod layout;
+mod memory;
+mod menu;
+mod net;
+mod quiz;
+mod reverse;
+mod settings;
+mod settings_menu;
+mod sprite;
+mod survival;
+mod time_attack;
+mod typing;
+mod versus;
+mod waves;
 
-use menu::draw_menu;
+use assets::Assets;
+use asymmetric::Asymmetric;
+use bindings::{Action, Bindings};
+use campaign::Campaign;
+use controls::draw_controls;
+use daily::Daily;
+use memory::Memory;
+use menu::{Menu, MenuChoice};
+use quiz::Quiz;
+use reverse::Reverse;
+use settings::Settings;
+use settings_menu::SettingsMenu;
+use survival::Survival;
+use time_attack::TimeAttack;
+use typing::Typing;
 use game1::Game as Game1;
 use game2::Game as Game2;
+use hotseat::HotSeat;
+use lan::Lan;
+use versus::Versus;
+use waves::Waves;
 
 #[derive(PartialEq)]
 enum GameState {
     Menu,
+    Controls,
+    Settings,
     Playing1,
     Playing2,
+    HotSeat,
+    Versus,
+    Asymmetric,
+    Reverse,
+    Lan,
+    Survival,
+    Zen,
+    TimeAttack,
+    Daily,
+    Campaign,
+    Waves,
+    Memory,
+    Quiz,
+    Typing,
 }
 
 #[macroquad::main("Whac-A-Mole")]
 async fn main() {
-    // Load assets
-    let background_image = load_image("images/background1.png")
-        .await
-        .expect("Failed to load background1 image");
-    let background_texture = Arc::new(Texture2D::from_image(&background_image));
-    
-    let mole_image = load_image("images/mole.png")
-        .await
-        .expect("Failed to load mole image");
-    let mole_texture = Arc::new(Texture2D::from_image(&mole_image));
-    
-    let hemlet_mole_image = load_image("images/helmet_mole.png")
-        .await
-        .expect("Failed to load helmet_mole image");
-    let hemlet_mole_texture = Arc::new(Texture2D::from_image(&hemlet_mole_image));
-
-    let cat_image = load_image("images/cat.png")
-        .await
-        .expect("Failed to load cat image");
-    let cat_texture = Arc::new(Texture2D::from_image(&cat_image));
-    
+    // Seeds for new rounds come from macroquad's generator, which starts the same every launch
+    rand::srand(miniquad::date::now().to_bits());
+
+    // Load settings and assets
+    let mut settings = Settings::load();
+    let mut bindings = Bindings::load();
+    let assets = Arc::new(Assets::load().await);
+
     // Game state
     let mut game_state = GameState::Menu;
+    let mut menu = Menu::new();
+    let mut settings_menu = SettingsMenu::new();
     let mut current_game1: Option<Game1> = None;
     let mut current_game2: Option<Game2> = None;
+    let mut current_hotseat: Option<HotSeat> = None;
+    let mut current_versus: Option<Versus> = None;
+    let mut current_asymmetric: Option<Asymmetric> = None;
+    let mut current_reverse: Option<Reverse> = None;
+    let mut current_lan: Option<Lan> = None;
+    let mut current_survival: Option<Survival> = None;
+    let mut current_zen: Option<Game2> = None;
+    let mut current_time_attack: Option<TimeAttack> = None;
+    let mut current_daily: Option<Daily> = None;
+    let mut current_campaign: Option<Campaign> = None;
+    let mut current_waves: Option<Waves> = None;
+    let mut current_memory: Option<Memory> = None;
+    let mut current_quiz: Option<Quiz> = None;
+    let mut current_typing: Option<Typing> = None;
 
     loop {
         clear_background(LIGHTGRAY);
+        gamepad::update();
+        // A key pressed while the settings screen waits for a new binding only rebinds,
+        // and a letter typed during a typing round is only typed
+        let capturing_input = match game_state {
+            GameState::Settings => settings_menu.is_rebinding(),
+            GameState::Typing => current_typing.as_ref().is_some_and(|typing| typing.captures(Action::Quit)),
+            _ => false,
+        };
+        input::update(&bindings);
 
         match game_state {
             GameState::Menu => {
-                if let Some(difficulty) = draw_menu() {
-                    if difficulty == 1 {
-                        current_game1 = Some(Game1::new(difficulty, background_texture.clone(), mole_texture.clone()));
+                match menu.draw(&mut settings) {
+                    Some(MenuChoice::Play(1)) => {
+                        current_game1 = Some(Game1::new(1, assets.clone(), &settings, &bindings));
                         game_state = GameState::Playing1;
-                    } else if difficulty == 2 {
-                        current_game2 = Some(Game2::new(difficulty, background_texture.clone(), mole_texture.clone(), hemlet_mole_texture.clone(), cat_texture.clone()));
+                    }
+                    Some(MenuChoice::Play(2)) => {
+                        current_game2 = Some(Game2::new(2, assets.clone(), &settings, &bindings));
                         game_state = GameState::Playing2;
-                    }// no hard mode in this version
+                    }
+                    Some(MenuChoice::HotSeat) => {
+                        current_hotseat = Some(HotSeat::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::HotSeat;
+                    }
+                    Some(MenuChoice::Versus) => {
+                        current_versus = Some(Versus::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Versus;
+                    }
+                    Some(MenuChoice::Asymmetric) => {
+                        current_asymmetric = Some(Asymmetric::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Asymmetric;
+                    }
+                    Some(MenuChoice::Reverse) => {
+                        current_reverse = Some(Reverse::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Reverse;
+                    }
+                    Some(MenuChoice::Lan) => {
+                        current_lan = Some(Lan::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Lan;
+                    }
+                    Some(MenuChoice::Survival) => {
+                        current_survival = Some(Survival::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Survival;
+                    }
+                    Some(MenuChoice::Zen) => {
+                        current_zen = Some(Game2::new(2, assets.clone(), &settings, &bindings).with_zen(settings.zen_spawn_rate));
+                        game_state = GameState::Zen;
+                    }
+                    Some(MenuChoice::TimeAttack) => {
+                        current_time_attack = Some(TimeAttack::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::TimeAttack;
+                    }
+                    Some(MenuChoice::Daily) => {
+                        current_daily = Some(Daily::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Daily;
+                    }
+                    Some(MenuChoice::Campaign) => {
+                        current_campaign = Some(Campaign::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Campaign;
+                    }
+                    Some(MenuChoice::Waves) => {
+                        current_waves = Some(Waves::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Waves;
+                    }
+                    Some(MenuChoice::Memory) => {
+                        current_memory = Some(Memory::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Memory;
+                    }
+                    Some(MenuChoice::Quiz) => {
+                        current_quiz = Some(Quiz::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Quiz;
+                    }
+                    Some(MenuChoice::Typing) => {
+                        current_typing = Some(Typing::new(assets.clone(), &settings, &bindings));
+                        game_state = GameState::Typing;
+                    }
+                    Some(MenuChoice::Controls) => {
+                        game_state = GameState::Controls;
+                    }
+                    Some(MenuChoice::Settings) => {
+                        settings_menu = SettingsMenu::new();
+                        game_state = GameState::Settings;
+                    }
+                    Some(MenuChoice::Quit) => break,
+                    _ => {} // no hard mode in this version
+                }
+            }
+
+            GameState::Controls => {
+                if draw_controls(&bindings) {
+                    game_state = GameState::Menu;
+                }
+            }
+
+            GameState::Settings => {
+                if settings_menu.draw(&mut settings, &mut bindings) {
+                    game_state = GameState::Menu;
                 }
             }
 
             GameState::Playing1 => {
+                if bindings.pressed(Action::Restart) {
+                    current_game1 = Some(Game1::new(1, assets.clone(), &settings, &bindings));
+                }
                 if let Some(ref mut game) = current_game1 {
                     let return_to_menu = game.update();
                     game.draw();
@@ -73,6 +228,9 @@ async fn main() {
             }
 
             GameState::Playing2 => {
+                if bindings.pressed(Action::Restart) {
+                    current_game2 = Some(Game2::new(2, assets.clone(), &settings, &bindings));
+                }
                 if let Some(ref mut game) = current_game2 {
                     let return_to_menu = game.update();
                     game.draw();
@@ -83,9 +241,210 @@ async fn main() {
                     }
                 }
             }
+
+            GameState::HotSeat => {
+                if let Some(ref mut hotseat) = current_hotseat {
+                    let return_to_menu = hotseat.update();
+                    hotseat.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_hotseat = None;
+                    }
+                }
+            }
+
+            GameState::Versus => {
+                if bindings.pressed(Action::Restart) {
+                    current_versus = Some(Versus::new(assets.clone(), &settings, &bindings));
+                }
+                if let Some(ref mut versus) = current_versus {
+                    let return_to_menu = versus.update();
+                    versus.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_versus = None;
+                    }
+                }
+            }
+
+            GameState::Asymmetric => {
+                if bindings.pressed(Action::Restart) {
+                    current_asymmetric = Some(Asymmetric::new(assets.clone(), &settings, &bindings));
+                }
+                if let Some(ref mut asymmetric) = current_asymmetric {
+                    let return_to_menu = asymmetric.update();
+                    asymmetric.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_asymmetric = None;
+                    }
+                }
+            }
+
+            GameState::Reverse => {
+                // Restarting keeps the difficulty, so the mode handles it itself
+                if let Some(ref mut reverse) = current_reverse {
+                    let return_to_menu = reverse.update();
+                    reverse.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_reverse = None;
+                    }
+                }
+            }
+
+            GameState::Lan => {
+                // No restarts, the host starts every round
+                if let Some(ref mut lan) = current_lan {
+                    let return_to_menu = lan.update();
+                    lan.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_lan = None;
+                    }
+                }
+            }
+
+            GameState::Survival => {
+                if bindings.pressed(Action::Restart) {
+                    current_survival = Some(Survival::new(assets.clone(), &settings, &bindings));
+                }
+                if let Some(ref mut survival) = current_survival {
+                    let return_to_menu = survival.update();
+                    survival.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_survival = None;
+                    }
+                }
+            }
+
+            GameState::Zen => {
+                if bindings.pressed(Action::Restart) {
+                    current_zen = Some(Game2::new(2, assets.clone(), &settings, &bindings).with_zen(settings.zen_spawn_rate));
+                }
+                if let Some(ref mut game) = current_zen {
+                    let return_to_menu = game.update();
+                    game.draw();
+
+                    // Keep the rate picked with -/+ for restarts and next time
+                    let spawn_rate = game.spawn_rate() as f32;
+                    if spawn_rate != settings.zen_spawn_rate {
+                        settings.zen_spawn_rate = spawn_rate;
+                        settings.save();
+                    }
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_zen = None;
+                    }
+                }
+            }
+
+            GameState::TimeAttack => {
+                if bindings.pressed(Action::Restart) {
+                    current_time_attack = Some(TimeAttack::new(assets.clone(), &settings, &bindings));
+                }
+                if let Some(ref mut time_attack) = current_time_attack {
+                    let return_to_menu = time_attack.update();
+                    time_attack.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_time_attack = None;
+                    }
+                }
+            }
+
+            GameState::Daily => {
+                // Restarts are handled inside, they decide which run is the official one
+                if let Some(ref mut daily) = current_daily {
+                    let return_to_menu = daily.update();
+                    daily.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_daily = None;
+                    }
+                }
+            }
+
+            GameState::Campaign => {
+                // Restarts are handled inside, they replay the current level
+                if let Some(ref mut campaign) = current_campaign {
+                    let return_to_menu = campaign.update();
+                    campaign.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_campaign = None;
+                    }
+                }
+            }
+
+            GameState::Waves => {
+                if bindings.pressed(Action::Restart) {
+                    current_waves = Some(Waves::new(assets.clone(), &settings, &bindings));
+                }
+                if let Some(ref mut waves) = current_waves {
+                    let return_to_menu = waves.update();
+                    waves.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_waves = None;
+                    }
+                }
+            }
+
+            GameState::Memory => {
+                // Restarts are handled inside, they keep the chosen style
+                if let Some(ref mut memory) = current_memory {
+                    let return_to_menu = memory.update();
+                    memory.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_memory = None;
+                    }
+                }
+            }
+
+            GameState::Quiz => {
+                // Restarts are handled inside, they keep the chosen difficulty
+                if let Some(ref mut quiz) = current_quiz {
+                    let return_to_menu = quiz.update();
+                    quiz.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_quiz = None;
+                    }
+                }
+            }
+
+            GameState::Typing => {
+                // Restarts are handled inside, the restart key is a letter to type
+                if let Some(ref mut typing) = current_typing {
+                    let return_to_menu = typing.update();
+                    typing.draw();
+
+                    if return_to_menu {
+                        game_state = GameState::Menu;
+                        current_typing = None;
+                    }
+                }
+            }
         }
 
-        if is_key_pressed(KeyCode::Escape) {
+        // Quit from anywhere except while picking a new binding or typing
+        if !capturing_input && bindings.pressed(Action::Quit) {
             break;
         }
 
@@ -0,0 +1,359 @@
+use macroquad::prelude::*;
+use macroquad::rand::ChooseRandom;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::clock::Clock;
+use crate::game1::Mole;
+use crate::gamepad::{self, Button};
+use crate::highscores::{HighScores, Run};
+use crate::hole;
+use crate::hud::{self, Popups};
+use crate::input::{self, PressKind};
+use crate::keyboard;
+use crate::layout;
+use crate::menu::{draw_options, option_buttons, pick_option};
+use crate::settings::Settings;
+
+const LIVES: u32 = 3;
+const START_LENGTH: usize = 3;
+const MAX_SET_LENGTH: usize = 8; // A set of all nine holes would be no test
+const BOARD_SCALE: f32 = 0.7;
+const FLASH_TIME: f64 = 0.6; // Sequence: how long each mole is up
+const FLASH_GAP: f64 = 0.25; // Sequence: pause between two moles
+const SET_TIME: f64 = 0.8;   // Set: how long the whole set is up, plus SET_TIME_PER_MOLE each
+const SET_TIME_PER_MOLE: f64 = 0.3;
+const PEEK_TIME: f64 = 0.35; // A picked hole shows its mole this long
+const BREAK_TIME: f64 = 1.2; // Between a round's end and the next flash
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Sequence, // Pick the holes in the order they flashed
+    Set,      // They all flash together, pick them in any order
+}
+
+const STYLES: [(&str, Style, &str); 2] = [
+    ("Sequence", Style::Sequence, "moles flash one by one, hit the holes in the same order"),
+    ("Set", Style::Set, "moles flash all at once, hit every one of those holes"),
+];
+
+enum Phase {
+    Showing { start: f64 },
+    Recall { picked: Vec<usize> }, // Right picks so far
+    Break { until: f64 },
+    Over,
+}
+
+struct Round {
+    style: Style,
+    holes: Vec<Mole>,
+    flashed: Vec<bool>,        // Moles up as part of the flash
+    hide_at: Vec<Option<f64>>, // When a peeking mole goes back down
+    sequence: Vec<usize>,
+    phase: Phase,
+    score: i32,
+    longest: usize, // Longest sequence recalled
+    lives: u32,
+    clock: Clock, // Stopped, and the run recorded, once the game is over
+}
+
+// Memory mode: moles flash in some of the 3x3 holes and hide again, then the player hits
+// those holes from memory, with one more mole every time they get it right.
+pub struct Memory {
+    assets: Arc<Assets>,
+    bindings: Bindings,
+    hit_leniency: f32,
+    scale: f32,
+    positions: Vec<(f32, f32)>,
+    hole_keys: Vec<Input>,
+    highscores: HighScores,
+    round: Option<Round>, // None while picking a style
+    popups: Popups,
+}
+
+impl Memory {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let scale = settings.easy_scale * BOARD_SCALE;
+        Self {
+            assets,
+            bindings: bindings.clone(),
+            hit_leniency: settings.hit_leniency,
+            scale,
+            positions: layout::grid(3, 3, scale),
+            hole_keys: keyboard::hole_inputs(3, 3, bindings),
+            highscores: HighScores::load(),
+            round: None,
+            popups: Popups::new(),
+        }
+    }
+
+    fn start(&mut self, style: Style) {
+        let mut round = Round {
+            style,
+            holes: self.positions.iter().map(|&(x, y)| Mole::new(x, y)).collect(),
+            flashed: vec![false; self.positions.len()],
+            hide_at: vec![None; self.positions.len()],
+            sequence: Vec::new(),
+            phase: Phase::Break { until: get_time() + BREAK_TIME },
+            score: 0,
+            longest: 0,
+            lives: LIVES,
+            clock: Clock::start(),
+        };
+        round.sequence = round.new_sequence(START_LENGTH);
+        self.round = Some(round);
+        self.popups = Popups::new();
+    }
+
+    fn mode(style: Style) -> &'static str {
+        match style {
+            Style::Sequence => "memory-sequence",
+            Style::Set => "memory-set",
+        }
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        let Some(round) = &mut self.round else {
+            if let Some(i) = pick_option(&option_buttons(STYLES.len())) {
+                self.start(STYLES[i].1);
+            }
+            return back_to_menu;
+        };
+
+        if self.bindings.pressed(Action::Restart) {
+            let style = round.style;
+            self.start(style);
+            return back_to_menu;
+        }
+        if self.bindings.pressed(Action::Pause) || gamepad::is_button_pressed(Button::Start) {
+            round.toggle_pause();
+        }
+        if round.clock.is_paused() {
+            return back_to_menu;
+        }
+
+        // Holes can be picked whether or not a mole is up in them
+        let mut picks = Vec::new();
+        if let Some(hole) = keyboard::pressed_hole(&self.hole_keys) {
+            picks.push(hole);
+        }
+        for press in input::presses() {
+            if press.kind != PressKind::Primary {
+                continue;
+            }
+            let hole = round.holes.iter().position(|mole| {
+                mole.is_hole_clicked(press.x, press.y, &self.assets.mole, self.scale, self.hit_leniency)
+            });
+            picks.extend(hole);
+        }
+
+        round.update_moles();
+        for hole in picks {
+            round.pick(hole, &mut self.popups);
+        }
+        round.update_phase();
+        if matches!(round.phase, Phase::Over) && !round.clock.is_over() {
+            round.clock.end();
+            let run = Run { score: round.longest as i32, seconds: round.clock.elapsed() };
+            self.highscores.record(Self::mode(round.style), run);
+            self.highscores.save();
+        }
+        self.popups.update();
+
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        let Some(round) = &self.round else {
+            draw_text("MEMORY", 60.0, 100.0, 60.0, BLACK);
+            draw_text("Moles flash in some holes and hide again. Hit those holes from memory.", 60.0, 160.0, 28.0, DARKGRAY);
+            draw_text("Pick a style (1 / 2):", 60.0, 210.0, 28.0, DARKGRAY);
+            let options: Vec<(&str, Color)> = STYLES.iter().map(|&(label, _, _)| (label, PURPLE)).collect();
+            draw_options(&options);
+            for (i, (label, _, description)) in STYLES.iter().enumerate() {
+                draw_text(&format!("{}: {}", label, description), 60.0, 450.0 + i as f32 * 34.0, 26.0, DARKGRAY);
+            }
+            return;
+        };
+
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
+        let mut holes: Vec<&Mole> = round.holes.iter().collect();
+        holes.sort_by(|a, b| a.position().y.total_cmp(&b.position().y));
+        for mole in holes {
+            mole.draw(&self.assets, self.scale);
+        }
+        for (&(x, y), &input) in self.positions.iter().zip(&self.hole_keys) {
+            hole::draw_label(&self.assets.mole, vec2(x, y), self.scale, &keyboard::key_label(input));
+        }
+        self.popups.draw();
+
+        draw_text(&format!("Score: {}", round.score), 20.0, 40.0, 40.0, WHITE);
+        draw_text(&format!("Length: {}", round.sequence.len()), 20.0, 80.0, 40.0, WHITE);
+        draw_text(&format!("Lives: {}", round.lives), 20.0, 120.0, 40.0, WHITE);
+
+        let status = match &round.phase {
+            Phase::Showing { .. } => "Watch...".to_string(),
+            Phase::Recall { picked } => format!("Your turn: {} / {}", picked.len(), round.sequence.len()),
+            Phase::Break { .. } => "Get ready".to_string(),
+            Phase::Over => {
+                let best = self.highscores.best_scores(Self::mode(round.style)).first().map_or(0, |run| run.score);
+                format!("Game over! Longest: {}   Best: {}   Score: {}", round.longest, best, round.score)
+            }
+        };
+        let color = if matches!(round.phase, Phase::Over) { RED } else { WHITE };
+        draw_text(&status, 20.0, screen_height() - 80.0, 30.0, color);
+        draw_text(
+            &format!(
+                "Click a hole or press its key. {} restarts, {} returns to menu, {} pauses.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+                self.bindings.label(Action::Pause),
+            ),
+            20.0,
+            screen_height() - 20.0,
+            20.0,
+            DARKGRAY,
+        );
+
+        if round.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, true);
+        }
+    }
+}
+
+impl Round {
+    fn toggle_pause(&mut self) {
+        let Some(paused_for) = self.clock.toggle_pause() else {
+            return;
+        };
+        for mole in &mut self.holes {
+            mole.delay(paused_for);
+        }
+        for hide_at in self.hide_at.iter_mut().flatten() {
+            *hide_at += paused_for;
+        }
+        match &mut self.phase {
+            Phase::Showing { start } => *start += paused_for,
+            Phase::Break { until } => *until += paused_for,
+            Phase::Recall { .. } | Phase::Over => {}
+        }
+    }
+
+    fn new_sequence(&self, length: usize) -> Vec<usize> {
+        let count = self.holes.len();
+        match self.style {
+            Style::Sequence => {
+                // Never the same hole twice in a row, it would look like one long flash
+                let mut sequence: Vec<usize> = Vec::with_capacity(length);
+                while sequence.len() < length {
+                    let hole = rand::gen_range(0, count);
+                    if sequence.last() != Some(&hole) {
+                        sequence.push(hole);
+                    }
+                }
+                sequence
+            }
+            Style::Set => {
+                let mut holes: Vec<usize> = (0..count).collect();
+                holes.shuffle();
+                holes.truncate(length.min(MAX_SET_LENGTH));
+                holes
+            }
+        }
+    }
+
+    // Which holes show their mole `t` seconds into the flash, None once it's over
+    fn flashing(&self, t: f64) -> Option<Vec<usize>> {
+        match self.style {
+            Style::Sequence => {
+                let step = (t / (FLASH_TIME + FLASH_GAP)) as usize;
+                let hole = *self.sequence.get(step)?;
+                let up = t - step as f64 * (FLASH_TIME + FLASH_GAP) < FLASH_TIME;
+                Some(if up { vec![hole] } else { Vec::new() })
+            }
+            Style::Set => {
+                let show_for = SET_TIME + SET_TIME_PER_MOLE * self.sequence.len() as f64;
+                (t < show_for).then(|| self.sequence.clone())
+            }
+        }
+    }
+
+    // Send peeking moles back down
+    fn update_moles(&mut self) {
+        let now = get_time();
+        for (mole, hide_at) in self.holes.iter_mut().zip(&mut self.hide_at) {
+            if hide_at.is_some_and(|at| now >= at) {
+                mole.hide();
+                *hide_at = None;
+            }
+        }
+    }
+
+    fn update_phase(&mut self) {
+        let now = get_time();
+        match &self.phase {
+            Phase::Showing { start } => match self.flashing(now - start) {
+                Some(up) => {
+                    for (i, mole) in self.holes.iter_mut().enumerate() {
+                        let should_be_up = up.contains(&i);
+                        if should_be_up != self.flashed[i] {
+                            if should_be_up { mole.show() } else { mole.hide() }
+                            self.flashed[i] = should_be_up;
+                        }
+                    }
+                }
+                None => {
+                    for (mole, flashed) in self.holes.iter_mut().zip(&mut self.flashed) {
+                        if *flashed {
+                            mole.hide();
+                            *flashed = false;
+                        }
+                    }
+                    self.phase = Phase::Recall { picked: Vec::new() };
+                }
+            },
+            Phase::Break { until } if now >= *until => self.phase = Phase::Showing { start: now },
+            _ => {}
+        }
+    }
+
+    fn pick(&mut self, hole: usize, popups: &mut Popups) {
+        let Phase::Recall { picked } = &mut self.phase else {
+            return;
+        };
+        // A set's holes only count once each, picking one again does nothing
+        if self.style == Style::Set && picked.contains(&hole) {
+            return;
+        }
+        let right = match self.style {
+            Style::Sequence => self.sequence.get(picked.len()) == Some(&hole),
+            Style::Set => self.sequence.contains(&hole),
+        };
+        let (x, y) = self.holes[hole].position().into();
+        self.holes[hole].show();
+        self.hide_at[hole] = Some(get_time() + PEEK_TIME);
+
+        if !right {
+            self.lives -= 1;
+            popups.spawn("Wrong!", x, y - 100.0, RED);
+            // Try the same moles again, or stop on the last life
+            self.phase = if self.lives == 0 { Phase::Over } else { Phase::Break { until: get_time() + BREAK_TIME } };
+            return;
+        }
+
+        popups.spawn("Yes!", x, y - 100.0, GREEN);
+        picked.push(hole);
+        if picked.len() == self.sequence.len() {
+            let length = self.sequence.len();
+            self.score += length as i32;
+            self.longest = self.longest.max(length);
+            popups.spawn(&format!("+{}", length), x, y - 140.0, GOLD);
+            self.sequence = self.new_sequence(length + 1);
+            self.phase = Phase::Break { until: get_time() + BREAK_TIME };
+        }
+    }
+}
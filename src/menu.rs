@@ -1,14 +1,76 @@
 use macroquad::prelude::*;
 
+use crate::gamepad::{self, Button};
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuChoice {
+    Play(u8), // Difficulty
+    HotSeat,
+    Versus,
+    Asymmetric,
+    Reverse,
+    Lan,
+    Survival,
+    Zen,
+    TimeAttack,
+    Daily,
+    Campaign,
+    Waves,
+    Memory,
+    Quiz,
+    Typing,
+    Controls,
+    Settings,
+    Quit,
+}
+
+// Menu buttons in display order: label, color, what picking it does
+const ITEMS: [(&str, Color, MenuChoice); 20] = [
+    ("Easy (1)", GREEN, MenuChoice::Play(1)),
+    ("Medium (2)", ORANGE, MenuChoice::Play(2)),
+    ("Hard (3)", RED, MenuChoice::Play(3)),
+    ("Hot-seat (H)", PURPLE, MenuChoice::HotSeat),
+    ("Versus (V)", PURPLE, MenuChoice::Versus),
+    ("Moles (M)", PURPLE, MenuChoice::Asymmetric),
+    ("Be the Mole (B)", BROWN, MenuChoice::Reverse),
+    ("LAN game (L)", DARKBLUE, MenuChoice::Lan),
+    ("Survival (E)", MAROON, MenuChoice::Survival),
+    ("Zen (Z)", DARKGREEN, MenuChoice::Zen),
+    ("Time Attack (T)", MAROON, MenuChoice::TimeAttack),
+    ("Daily (D)", DARKBLUE, MenuChoice::Daily),
+    ("Campaign (A)", BROWN, MenuChoice::Campaign),
+    ("Waves (W)", MAROON, MenuChoice::Waves),
+    ("Memory (Y)", PURPLE, MenuChoice::Memory),
+    ("Math Quiz (Q)", DARKBLUE, MenuChoice::Quiz),
+    ("Typing (X)", DARKBLUE, MenuChoice::Typing),
+    ("Controls (C)", GRAY, MenuChoice::Controls),
+    ("Settings (S)", GRAY, MenuChoice::Settings),
+    ("Quit", DARKGRAY, MenuChoice::Quit),
+];
+
+const BUTTON_WIDTH: f32 = 200.0;
+const BUTTON_HEIGHT: f32 = 60.0;
+const BUTTON_GAP: f32 = 20.0;
+const BUTTONS_TOP: f32 = 250.0;
+const SIDE_MARGIN: f32 = 20.0;
+const FOOTER_HEIGHT: f32 = 110.0; // Hint lines under the buttons
+
 pub fn draw_button(x: f32, y: f32, width: f32, height: f32, text: &str, color: Color) {
     draw_rectangle(x, y, width, height, color);
     draw_rectangle_lines(x, y, width, height, 3.0, BLACK);
-    let text_size = measure_text(text, None, 40, 1.0);
+    // Shrink long labels to fit the button
+    let mut font_size = 40;
+    let mut text_size = measure_text(text, None, font_size, 1.0);
+    while text_size.width > width - 16.0 && font_size > 16 {
+        font_size -= 2;
+        text_size = measure_text(text, None, font_size, 1.0);
+    }
     draw_text(
         text,
         x + (width - text_size.width) / 2.0,
         y + (height + text_size.height) / 2.0,
-        40.0,
+        font_size as f32,
         WHITE,
     );
 }
@@ -17,61 +79,220 @@ pub fn is_button_clicked(x: f32, y: f32, width: f32, height: f32, mouse_x: f32,
     mouse_x >= x && mouse_x <= x + width && mouse_y >= y && mouse_y <= y + height
 }
 
-pub fn draw_menu() -> Option<u8> {
-    // Draw title
-    draw_text(
-        "WHAC-A-MOLE",
-        screen_width() / 2.0 - 150.0,
-        100.0,
-        60.0,
-        BLACK,
-    );
+const NUMBER_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
 
-    draw_text(
-        "Select Difficulty:",
-        screen_width() / 2.0 - 120.0,
-        200.0,
-        40.0,
-        DARKGRAY,
-    );
+// Buttons for a mode's options (difficulty, style, ...) in a centred column
+pub fn option_buttons(count: usize) -> Vec<Rect> {
+    (0..count)
+        .map(|i| Rect::new(screen_width() / 2.0 - 120.0, 250.0 + i as f32 * 80.0, 240.0, 60.0))
+        .collect()
+}
 
-    // Draw difficulty buttons
-    let button_width = 200.0;
-    let button_height = 60.0;
-    let button_x = screen_width() / 2.0 - button_width / 2.0;
+pub fn draw_options(options: &[(&str, Color)]) {
+    for (rect, (label, color)) in option_buttons(options.len()).iter().zip(options) {
+        draw_button(rect.x, rect.y, rect.w, rect.h, label, *color);
+    }
+}
 
-    draw_button(button_x, 250.0, button_width, button_height, "Easy (1)", GREEN);
-    draw_button(button_x, 330.0, button_width, button_height, "Medium (2)", ORANGE);
-    draw_button(button_x, 410.0, button_width, button_height, "Hard (3)", RED);
+// The option picked this frame by pressing its number or clicking its button, if any
+pub fn pick_option(buttons: &[Rect]) -> Option<usize> {
+    if let Some(i) = NUMBER_KEYS.iter().take(buttons.len()).position(|&key| is_key_pressed(key)) {
+        return Some(i);
+    }
+    if !is_mouse_button_pressed(MouseButton::Left) {
+        return None;
+    }
+    let (mouse_x, mouse_y) = mouse_position();
+    buttons
+        .iter()
+        .position(|rect| is_button_clicked(rect.x, rect.y, rect.w, rect.h, mouse_x, mouse_y))
+}
 
-    draw_text(
-        "Click a button to start!",
-        screen_width() / 2.0 - 130.0,
-        520.0,
-        25.0,
-        DARKGRAY,
-    );
+// Columns, rows and button scale (at most 1.0) that fit `count` buttons best between
+// BUTTONS_TOP and the footer of a `width` x `height` window
+fn fit_grid(count: usize, width: f32, height: f32) -> (usize, usize, f32) {
+    let free_width = width - 2.0 * SIDE_MARGIN;
+    let free_height = height - BUTTONS_TOP - FOOTER_HEIGHT;
+    (1..=count)
+        .map(|columns| {
+            let rows = count.div_ceil(columns);
+            let grid_width = columns as f32 * (BUTTON_WIDTH + BUTTON_GAP) - BUTTON_GAP;
+            let grid_height = rows as f32 * (BUTTON_HEIGHT + BUTTON_GAP) - BUTTON_GAP;
+            (columns, rows, (free_width / grid_width).min(free_height / grid_height).min(1.0))
+        })
+        .fold((1, count, 0.0), |best, fit| if fit.2 > best.2 { fit } else { best })
+}
 
-    draw_text(
-        "Press Q to return to menu, ESC to quit.",
-        20.0,
-        screen_height() - 20.0,
-        20.0,
-        DARKGRAY,
-    );
+pub struct Menu {
+    selected: usize, // Button highlighted for gamepad navigation
+}
 
-    // Handle button clicks
-    if is_mouse_button_pressed(MouseButton::Left) {
-        let (mouse_x, mouse_y) = mouse_position();
+impl Menu {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
 
-        if is_button_clicked(button_x, 250.0, button_width, button_height, mouse_x, mouse_y) {
-            return Some(1); // Easy
-        } else if is_button_clicked(button_x, 330.0, button_width, button_height, mouse_x, mouse_y) {
-            return Some(2); // Medium
-        } else if is_button_clicked(button_x, 410.0, button_width, button_height, mouse_x, mouse_y) {
-            return Some(3); // Hard
+    // Buttons fill columns top to bottom, centered on the screen and shrunk to fit it
+    fn button_rect(index: usize) -> Rect {
+        let (_, rows, scale) = fit_grid(ITEMS.len(), screen_width(), screen_height());
+        let (width, height, gap) = (BUTTON_WIDTH * scale, BUTTON_HEIGHT * scale, BUTTON_GAP * scale);
+        let columns = ITEMS.len().div_ceil(rows);
+        let total_width = columns as f32 * (width + gap) - gap;
+        let col = index / rows;
+        let row = index % rows;
+        Rect::new(
+            screen_width() / 2.0 - total_width / 2.0 + col as f32 * (width + gap),
+            BUTTONS_TOP + row as f32 * (height + gap),
+            width,
+            height,
+        )
+    }
+
+    pub fn draw(&mut self, settings: &mut Settings) -> Option<MenuChoice> {
+        // Draw title
+        draw_text(
+            "WHAC-A-MOLE",
+            screen_width() / 2.0 - 150.0,
+            100.0,
+            60.0,
+            BLACK,
+        );
+
+        draw_text(
+            "Select Difficulty:",
+            screen_width() / 2.0 - 120.0,
+            200.0,
+            40.0,
+            DARKGRAY,
+        );
+
+        // Draw menu buttons, outlining the gamepad selection
+        let gamepad_connected = gamepad::connected_count() > 0;
+        for (i, (label, color, _)) in ITEMS.iter().enumerate() {
+            let rect = Self::button_rect(i);
+            draw_button(rect.x, rect.y, rect.w, rect.h, label, *color);
+            if gamepad_connected && i == self.selected {
+                draw_rectangle_lines(rect.x - 6.0, rect.y - 6.0, rect.w + 12.0, rect.h + 12.0, 4.0, YELLOW);
+            }
+        }
+
+        let (_, rows, _) = fit_grid(ITEMS.len(), screen_width(), screen_height());
+        let last_in_column = Self::button_rect(rows - 1);
+        let text_top = last_in_column.bottom() + 30.0;
+        draw_text(
+            "Click a button to start!",
+            screen_width() / 2.0 - 130.0,
+            text_top,
+            25.0,
+            DARKGRAY,
+        );
+
+        let keyboard_mode = if settings.keyboard_mode { "ON" } else { "OFF" };
+        draw_text(
+            &format!("Keyboard mode: {} (press K to toggle)", keyboard_mode),
+            screen_width() / 2.0 - 130.0,
+            text_top + 40.0,
+            25.0,
+            DARKGRAY,
+        );
+
+        if gamepad_connected {
+            draw_text(
+                "Gamepad connected: D-pad/stick to choose, A to select",
+                screen_width() / 2.0 - 130.0,
+                text_top + 80.0,
+                25.0,
+                DARKGRAY,
+            );
+        }
+
+        // Handle gamepad navigation, up/down within a column and left/right across columns
+        if let Some((dx, dy)) = gamepad::direction_pressed() {
+            let step = dy + dx * rows as i32;
+            self.selected = (self.selected as i32 + step).rem_euclid(ITEMS.len() as i32) as usize;
+        }
+        if gamepad::is_button_pressed(Button::A) {
+            return Some(ITEMS[self.selected].2);
         }
+
+        // Handle keyboard shortcuts
+        if is_key_pressed(KeyCode::K) {
+            settings.keyboard_mode = !settings.keyboard_mode;
+            settings.save();
+        }
+        if is_key_pressed(KeyCode::Key1) {
+            return Some(MenuChoice::Play(1));
+        } else if is_key_pressed(KeyCode::Key2) {
+            return Some(MenuChoice::Play(2));
+        } else if is_key_pressed(KeyCode::Key3) {
+            return Some(MenuChoice::Play(3));
+        } else if is_key_pressed(KeyCode::H) {
+            return Some(MenuChoice::HotSeat);
+        } else if is_key_pressed(KeyCode::V) {
+            return Some(MenuChoice::Versus);
+        } else if is_key_pressed(KeyCode::M) {
+            return Some(MenuChoice::Asymmetric);
+        } else if is_key_pressed(KeyCode::B) {
+            return Some(MenuChoice::Reverse);
+        } else if is_key_pressed(KeyCode::L) {
+            return Some(MenuChoice::Lan);
+        } else if is_key_pressed(KeyCode::E) {
+            return Some(MenuChoice::Survival);
+        } else if is_key_pressed(KeyCode::Z) {
+            return Some(MenuChoice::Zen);
+        } else if is_key_pressed(KeyCode::T) {
+            return Some(MenuChoice::TimeAttack);
+        } else if is_key_pressed(KeyCode::D) {
+            return Some(MenuChoice::Daily);
+        } else if is_key_pressed(KeyCode::A) {
+            return Some(MenuChoice::Campaign);
+        } else if is_key_pressed(KeyCode::W) {
+            return Some(MenuChoice::Waves);
+        } else if is_key_pressed(KeyCode::Y) {
+            return Some(MenuChoice::Memory);
+        } else if is_key_pressed(KeyCode::Q) {
+            return Some(MenuChoice::Quiz);
+        } else if is_key_pressed(KeyCode::X) {
+            return Some(MenuChoice::Typing);
+        } else if is_key_pressed(KeyCode::C) {
+            return Some(MenuChoice::Controls);
+        } else if is_key_pressed(KeyCode::S) {
+            return Some(MenuChoice::Settings);
+        }
+
+        // Handle button clicks
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (mouse_x, mouse_y) = mouse_position();
+
+            for (i, (_, _, choice)) in ITEMS.iter().enumerate() {
+                let rect = Self::button_rect(i);
+                if is_button_clicked(rect.x, rect.y, rect.w, rect.h, mouse_x, mouse_y) {
+                    return Some(*choice);
+                }
+            }
+        }
+
+        None
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    None
+    #[test]
+    fn the_buttons_fit_the_default_window() {
+        let (columns, rows, scale) = fit_grid(ITEMS.len(), 800.0, 600.0);
+        assert!(columns * rows >= ITEMS.len());
+        let width = columns as f32 * (BUTTON_WIDTH + BUTTON_GAP) * scale - BUTTON_GAP * scale;
+        let height = rows as f32 * (BUTTON_HEIGHT + BUTTON_GAP) * scale - BUTTON_GAP * scale;
+        assert!(width <= 800.0 - 2.0 * SIDE_MARGIN);
+        assert!(BUTTONS_TOP + height + FOOTER_HEIGHT <= 600.0);
+
+        // Full size when there is room
+        assert_eq!(fit_grid(ITEMS.len(), 1920.0, 1080.0).2, 1.0);
+    }
 }
@@ -0,0 +1,372 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, Shutdown, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// LAN play over TCP. Every message is one line of text; the host accepts players on a
+// background thread and each connection gets a reader thread feeding a channel and a
+// writer thread fed by one, so the game loop only ever polls and queues and never blocks.
+
+pub const DEFAULT_PORT: u16 = 7878;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const ACCEPT_POLL: Duration = Duration::from_millis(50);
+// Lines queued for a peer that isn't reading before it gets disconnected
+const WRITE_QUEUE: usize = 256;
+const NAME_LENGTH: usize = 24; // Characters, like the name field in the lobby
+
+// This machine's address on the local network, to tell the other players.
+// Connecting a UDP socket only picks a route, nothing is sent.
+pub fn local_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("8.8.8.8", 80)).ok()?;
+    socket.local_addr().ok().map(|address| address.ip())
+}
+
+// A player's name as the host keeps it: no tabs or other control characters, which would
+// break the BOARD line, and no longer than NAME_LENGTH. None if nothing is left.
+pub fn clean_name(name: &str) -> Option<String> {
+    let name: String = name.chars().filter(|c| !c.is_control()).take(NAME_LENGTH).collect();
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+// One player's line on the scoreboard
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub id: usize,
+    pub name: String,
+    pub score: i32,
+    pub done: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Hello { name: String },              // Player -> host: join with this name
+    Welcome { id: usize },               // Host -> player: your id on the scoreboard
+    Start { seed: u64, duration: f64, frenzy: f32 }, // Host -> players: start a Medium round on this seed
+    Hit { delta: i32, score: i32 },      // Player -> host: score changed by `delta`
+    Done { score: i32 },                 // Player -> host: round over with this score
+    Board(Vec<Standing>),                // Host -> players: the live scoreboard
+}
+
+impl Message {
+    fn to_line(&self) -> String {
+        match self {
+            Message::Hello { name } => format!("HELLO {}", name),
+            Message::Welcome { id } => format!("WELCOME {}", id),
+            Message::Start { seed, duration, frenzy } => format!("START {} {} {}", seed, duration, frenzy),
+            Message::Hit { delta, score } => format!("HIT {} {}", delta, score),
+            Message::Done { score } => format!("DONE {}", score),
+            Message::Board(standings) => {
+                // Names go last in each entry since they may contain spaces or colons
+                let entries: Vec<String> = standings
+                    .iter()
+                    .map(|s| format!("{}:{}:{}:{}", s.id, s.score, s.done as u8, s.name))
+                    .collect();
+                format!("BOARD {}", entries.join("\t"))
+            }
+        }
+    }
+
+    fn parse(line: &str) -> Option<Message> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let field = |i: usize| fields.get(i).copied().unwrap_or("");
+        match command {
+            "HELLO" => Some(Message::Hello { name: rest.to_string() }),
+            "WELCOME" => Some(Message::Welcome { id: field(0).parse().ok()? }),
+            "START" => Some(Message::Start {
+                seed: field(0).parse().ok()?,
+                duration: field(1).parse().ok()?,
+                frenzy: field(2).parse().ok()?,
+            }),
+            "HIT" => Some(Message::Hit { delta: field(0).parse().ok()?, score: field(1).parse().ok()? }),
+            "DONE" => Some(Message::Done { score: field(0).parse().ok()? }),
+            "BOARD" => {
+                let mut standings = Vec::new();
+                for entry in rest.split('\t').filter(|entry| !entry.is_empty()) {
+                    let mut parts = entry.splitn(4, ':');
+                    standings.push(Standing {
+                        id: parts.next()?.parse().ok()?,
+                        score: parts.next()?.parse().ok()?,
+                        done: parts.next()? == "1",
+                        name: parts.next()?.to_string(),
+                    });
+                }
+                Some(Message::Board(standings))
+            }
+            _ => None,
+        }
+    }
+}
+
+// The sending half of a connection: lines are written on their own thread
+struct Writer {
+    lines: SyncSender<String>,
+    stream: TcpStream,
+}
+
+impl Writer {
+    fn spawn(stream: TcpStream) -> io::Result<Writer> {
+        let mut out = stream.try_clone()?;
+        let (lines, queue) = mpsc::sync_channel::<String>(WRITE_QUEUE);
+        thread::spawn(move || {
+            for line in queue {
+                // A failed write means the peer is gone, its reader thread reports that
+                if writeln!(out, "{}", line).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Writer { lines, stream })
+    }
+
+    fn send(&self, message: &Message) {
+        // A peer that stopped reading is cut off rather than queued for forever
+        if self.lines.try_send(message.to_line()).is_err() {
+            let _ = self.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// Read messages off `stream` until it closes, then send `None`
+fn spawn_reader<T: Send + 'static>(stream: TcpStream, events: Sender<T>, wrap: impl Fn(Option<Message>) -> T + Send + 'static) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if let Some(message) = Message::parse(line.trim_end()) {
+                if events.send(wrap(Some(message))).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = events.send(wrap(None));
+    });
+}
+
+// Something that happened on the host's side since the last poll
+#[derive(Debug, PartialEq)]
+pub enum HostEvent {
+    Joined(usize),
+    Message(usize, Message),
+    Left(usize),
+}
+
+pub struct Host {
+    port: u16,
+    clients: Arc<Mutex<Vec<(usize, Writer)>>>,
+    events: Receiver<HostEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Host {
+    // Listen on every interface; port 0 picks a free port
+    pub fn start(port: u16) -> io::Result<Host> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, events) = mpsc::channel();
+
+        let (thread_clients, thread_stop) = (clients.clone(), stop.clone());
+        thread::spawn(move || {
+            // Id 0 is the host's own player
+            let mut next_id = 1;
+            while !thread_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let (Ok(()), Ok(reader)) = (stream.set_nonblocking(false), stream.try_clone()) else {
+                            continue;
+                        };
+                        let _ = stream.set_nodelay(true);
+                        let Ok(writer) = Writer::spawn(stream) else {
+                            continue;
+                        };
+                        let id = next_id;
+                        next_id += 1;
+                        thread_clients.lock().unwrap().push((id, writer));
+                        if sender.send(HostEvent::Joined(id)).is_err() {
+                            return;
+                        }
+                        spawn_reader(reader, sender.clone(), move |message| match message {
+                            Some(message) => HostEvent::Message(id, message),
+                            None => HostEvent::Left(id),
+                        });
+                    }
+                    // Nobody waiting (or a failed accept), check the stop flag again shortly
+                    Err(_) => thread::sleep(ACCEPT_POLL),
+                }
+            }
+        });
+
+        Ok(Host { port, clients, events, stop })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn poll(&self) -> Vec<HostEvent> {
+        let events: Vec<HostEvent> = self.events.try_iter().collect();
+        // Stop writing to players that left
+        let left: Vec<usize> = events
+            .iter()
+            .filter_map(|event| match event {
+                HostEvent::Left(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        if !left.is_empty() {
+            self.clients.lock().unwrap().retain(|(id, _)| !left.contains(id));
+        }
+        events
+    }
+
+    pub fn send(&self, to: usize, message: &Message) {
+        for (id, writer) in self.clients.lock().unwrap().iter() {
+            if *id == to {
+                writer.send(message);
+            }
+        }
+    }
+
+    pub fn broadcast(&self, message: &Message) {
+        for (_, writer) in self.clients.lock().unwrap().iter() {
+            writer.send(message);
+        }
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Dropping the writers closes the connections
+        self.clients.lock().unwrap().clear();
+    }
+}
+
+pub struct Client {
+    writer: Writer,
+    events: Receiver<Option<Message>>, // None once the host has gone
+}
+
+impl Client {
+    // Blocks for up to a few seconds, call it off the game loop
+    pub fn connect(address: &str) -> io::Result<Client> {
+        let with_port = if address.contains(':') { address.to_string() } else { format!("{}:{}", address, DEFAULT_PORT) };
+        let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to");
+        for socket_address in with_port.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    let (sender, events) = mpsc::channel();
+                    spawn_reader(stream.try_clone()?, sender, |message| message);
+                    return Ok(Client { writer: Writer::spawn(stream)?, events });
+                }
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+
+    // Messages since the last poll; `None` means the connection closed
+    pub fn poll(&self) -> Vec<Option<Message>> {
+        self.events.try_iter().collect()
+    }
+
+    pub fn send(&self, message: &Message) {
+        self.writer.send(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn messages_survive_a_round_trip() {
+        let messages = [
+            Message::Hello { name: "Ada: the hammer".to_string() },
+            Message::Welcome { id: 3 },
+            Message::Start { seed: u64::MAX - 1, duration: 60.0, frenzy: 7.5 },
+            Message::Hit { delta: -5, score: 12 },
+            Message::Done { score: -3 },
+            Message::Board(vec![
+                Standing { id: 0, name: "Host".to_string(), score: 7, done: false },
+                Standing { id: 2, name: "Bo b:1".to_string(), score: -1, done: true },
+            ]),
+        ];
+        for message in messages {
+            assert_eq!(Message::parse(&message.to_line()), Some(message));
+        }
+    }
+
+    #[test]
+    fn hostile_names_still_fit_on_the_board() {
+        let name = clean_name(&format!("\tEve:1:1\u{7}\n{}", "x".repeat(100))).unwrap();
+        assert!(name.chars().count() <= NAME_LENGTH);
+        assert!(!name.chars().any(|c| c.is_control()));
+        let board = Message::Board(vec![
+            Standing { id: 1, name, score: 4, done: false },
+            Standing { id: 2, name: "Bob".to_string(), score: 2, done: true },
+        ]);
+        assert_eq!(Message::parse(&board.to_line()), Some(board));
+        assert_eq!(clean_name("\t \u{1b}"), None);
+    }
+
+    // Wait for `poll` to return something, the other end runs on its own threads
+    fn wait_for<T>(mut poll: impl FnMut() -> Vec<T>) -> Vec<T> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let events = poll();
+            if !events.is_empty() || Instant::now() > deadline {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn host_and_client_talk_over_localhost() {
+        let host = Host::start(0).unwrap();
+        let client = Client::connect(&format!("127.0.0.1:{}", host.port())).unwrap();
+        assert_eq!(wait_for(|| host.poll()), vec![HostEvent::Joined(1)]);
+
+        client.send(&Message::Hello { name: "Bob".to_string() });
+        let hello = Message::Hello { name: "Bob".to_string() };
+        assert_eq!(wait_for(|| host.poll()), vec![HostEvent::Message(1, hello)]);
+
+        host.broadcast(&Message::Start { seed: 42, duration: 60.0, frenzy: 10.0 });
+        assert_eq!(wait_for(|| client.poll()), vec![Some(Message::Start { seed: 42, duration: 60.0, frenzy: 10.0 })]);
+
+        drop(client);
+        assert_eq!(wait_for(|| host.poll()), vec![HostEvent::Left(1)]);
+    }
+
+    #[test]
+    fn a_player_that_stops_reading_is_dropped_without_blocking_the_host() {
+        let host = Host::start(0).unwrap();
+        // Connected but never reads, so its socket buffers fill up
+        let _stalled = TcpStream::connect(("127.0.0.1", host.port())).unwrap();
+        assert_eq!(wait_for(|| host.poll()), vec![HostEvent::Joined(1)]);
+
+        let board = Message::Board(vec![Standing { id: 1, name: "x".repeat(1000), score: 0, done: false }]);
+        let start = Instant::now();
+        for _ in 0..20_000 {
+            host.broadcast(&board);
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(wait_for(|| host.poll()), vec![HostEvent::Left(1)]);
+    }
+}
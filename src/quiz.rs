@@ -0,0 +1,370 @@
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::clock::Clock;
+use crate::game1::Mole;
+use crate::game2;
+use crate::gamepad::{self, Button};
+use crate::hole;
+use crate::hud::{self, Notices, Popups};
+use crate::input::{self, PressKind};
+use crate::keyboard;
+use crate::layout;
+use crate::menu::{draw_options, option_buttons, pick_option};
+use crate::settings::Settings;
+
+const ROUND_TIME: f64 = 60.0;
+const BOARD_SCALE: f32 = 0.7;
+const RIGHT_POINTS: i32 = 1;  // Same as a normal mole
+const WRONG_PENALTY: i32 = 5; // Same as hitting a cat
+const ANSWER_CHANCE: f64 = 0.35; // Chance a mole carries the answer, when one already does
+
+// How long moles stay up at each difficulty, and how long holes stay empty (seconds)
+const UP_TIME: [(f64, f64); 3] = [(2.5, 4.0), (2.0, 3.5), (1.5, 3.0)];
+const DOWN_TIME: (f64, f64) = (0.4, 1.6);
+
+const DIFFICULTIES: [(&str, Color); 3] = [("Easy", GREEN), ("Medium", ORANGE), ("Hard", RED)];
+
+#[derive(Debug)]
+struct Question {
+    text: String,
+    answer: i32,
+}
+
+// A sum for the difficulty: Easy adds and subtracts small numbers, Medium brings in the
+// times tables, Hard goes up to 12 x 12 with division and two-digit sums
+fn question(difficulty: usize, rng: &RandGenerator) -> Question {
+    let pick = |low: i32, high: i32| rng.gen_range(low, high + 1);
+    let kinds = [2, 3, 4][difficulty];
+    let (a, op, b, answer) = match (difficulty, rng.gen_range(0, kinds)) {
+        (0, 0) => {
+            let (a, b) = (pick(1, 10), pick(1, 10));
+            (a, "+", b, a + b)
+        }
+        (0, _) => {
+            let (a, b) = (pick(2, 20), pick(1, 10));
+            let (a, b) = (a.max(b), a.min(b));
+            (a, "-", b, a - b)
+        }
+        (1, 0) => {
+            let (a, b) = (pick(2, 10), pick(2, 10));
+            (a, "×", b, a * b)
+        }
+        (1, 1) => {
+            let (a, b) = (pick(10, 50), pick(1, 50));
+            (a, "+", b, a + b)
+        }
+        (_, 0) => {
+            let (a, b) = (pick(3, 12), pick(3, 12));
+            (a, "×", b, a * b)
+        }
+        (_, 1) => {
+            let (b, answer) = (pick(2, 12), pick(2, 12));
+            (b * answer, "÷", b, answer)
+        }
+        (_, 2) => {
+            let (a, b) = (pick(10, 99), pick(10, 99));
+            (a, "+", b, a + b)
+        }
+        _ => {
+            let (a, b) = (pick(20, 99), pick(10, 50));
+            let (a, b) = (a.max(b), a.min(b));
+            (a, "-", b, a - b)
+        }
+    };
+    Question { text: format!("{} {} {}", a, op, b), answer }
+}
+
+// A believable wrong answer near the right one
+fn distractor(answer: i32, rng: &RandGenerator) -> i32 {
+    loop {
+        let number = answer + rng.gen_range(-10, 11);
+        if number != answer && number >= 0 {
+            return number;
+        }
+    }
+}
+
+struct Round {
+    difficulty: usize,
+    rng: RandGenerator,
+    holes: Vec<Mole>,
+    numbers: Vec<Option<i32>>, // What each visible mole carries
+    next_toggle: Vec<f64>,
+    question: Question,
+    score: i32,
+    clock: Clock,
+}
+
+// Math quiz: a sum at the top of the screen and a number on every mole. Whack the
+// answer to score and get a new sum; a wrong number costs as much as a cat.
+pub struct Quiz {
+    assets: Arc<Assets>,
+    bindings: Bindings,
+    hit_leniency: f32,
+    scale: f32,
+    positions: Vec<(f32, f32)>,
+    hole_keys: Vec<Input>,
+    round: Option<Round>, // None while picking a difficulty
+    popups: Popups,
+    notices: Notices,
+}
+
+impl Quiz {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let scale = settings.easy_scale * BOARD_SCALE;
+        Self {
+            assets,
+            bindings: bindings.clone(),
+            hit_leniency: settings.hit_leniency,
+            scale,
+            positions: layout::grid(3, 3, scale),
+            hole_keys: if settings.keyboard_mode { keyboard::hole_inputs(3, 3, bindings) } else { Vec::new() },
+            round: None,
+            popups: Popups::new(),
+            notices: Notices::new(),
+        }
+    }
+
+    fn start(&mut self, difficulty: usize) {
+        let rng = RandGenerator::new();
+        rng.srand(game2::random_seed());
+        let now = get_time();
+        self.round = Some(Round {
+            difficulty,
+            question: question(difficulty, &rng),
+            next_toggle: self.positions.iter().map(|_| now + rng.gen_range(DOWN_TIME.0, DOWN_TIME.1)).collect(),
+            rng,
+            holes: self.positions.iter().map(|&(x, y)| Mole::new(x, y)).collect(),
+            numbers: vec![None; self.positions.len()],
+            score: 0,
+            clock: Clock::start(),
+        });
+        self.popups = Popups::new();
+        self.notices = Notices::new();
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        let Some(round) = &mut self.round else {
+            if let Some(difficulty) = pick_option(&option_buttons(DIFFICULTIES.len())) {
+                self.start(difficulty);
+            }
+            return back_to_menu;
+        };
+
+        if self.bindings.pressed(Action::Restart) {
+            let difficulty = round.difficulty;
+            self.start(difficulty);
+            return back_to_menu;
+        }
+        if self.bindings.pressed(Action::Pause) || gamepad::is_button_pressed(Button::Start) {
+            round.toggle_pause();
+        }
+        if !round.clock.is_running() {
+            return back_to_menu;
+        }
+
+        round.update_moles();
+
+        let mut hits = Vec::new();
+        for press in input::presses() {
+            if press.kind == PressKind::Primary {
+                let hole = round.holes.iter().position(|mole| {
+                    mole.is_clicked(press.x, press.y, &self.assets.mole, self.scale, self.hit_leniency)
+                });
+                match hole {
+                    Some(hole) => hits.push(hole),
+                    None => self.popups.spawn("Miss", press.x, press.y, LIGHTGRAY),
+                }
+            }
+        }
+        if let Some(hole) = keyboard::pressed_hole(&self.hole_keys) {
+            hits.push(hole);
+        }
+        for hole in hits {
+            round.whack(hole, &mut self.popups, &mut self.notices);
+        }
+        self.popups.update();
+        self.notices.update();
+
+        if round.elapsed() >= ROUND_TIME {
+            round.clock.end();
+            // Only the holes with a mole up, hiding an empty one would flash a mole
+            for (mole, number) in round.holes.iter_mut().zip(&mut round.numbers) {
+                if number.take().is_some() {
+                    mole.hide();
+                }
+            }
+        }
+
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        let Some(round) = &self.round else {
+            draw_text("MATH QUIZ", 60.0, 100.0, 60.0, BLACK);
+            draw_text("Whack the mole carrying the answer. Wrong numbers cost as much as a cat.", 60.0, 160.0, 28.0, DARKGRAY);
+            draw_text("Pick how hard the sums are (1 / 2 / 3):", 60.0, 210.0, 28.0, DARKGRAY);
+            draw_options(&DIFFICULTIES);
+            return;
+        };
+
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
+        let mut holes: Vec<(&Mole, &Option<i32>)> = round.holes.iter().zip(&round.numbers).collect();
+        holes.sort_by(|a, b| a.0.position().y.total_cmp(&b.0.position().y));
+        for (mole, number) in holes {
+            mole.draw(&self.assets, self.scale);
+            if let Some(number) = number {
+                mole.draw_badge(&self.assets, self.scale, &number.to_string());
+            }
+        }
+        for (&(x, y), &input) in self.positions.iter().zip(&self.hole_keys) {
+            hole::draw_label(&self.assets.mole, vec2(x, y), self.scale, &keyboard::key_label(input));
+        }
+        self.popups.draw();
+
+        // The question, big and centered at the top
+        if !round.clock.is_over() {
+            let text = format!("{} = ?", round.question.text);
+            let size = measure_text(&text, None, 64, 1.0);
+            draw_rectangle(
+                (screen_width() - size.width) / 2.0 - 20.0,
+                14.0,
+                size.width + 40.0,
+                size.height + 30.0,
+                Color::new(0.0, 0.0, 0.0, 0.6),
+            );
+            draw_text(&text, (screen_width() - size.width) / 2.0, 26.0 + size.height, 64.0, WHITE);
+        }
+
+        draw_text(&format!("Score: {}", round.score), 20.0, 40.0, 40.0, WHITE);
+        draw_text(&format!("Time: {:.0}", (ROUND_TIME - round.elapsed()).max(0.0)), 20.0, 80.0, 40.0, WHITE);
+        if round.clock.is_over() {
+            draw_text(&format!("Time's up! Final Score: {}", round.score), 20.0, screen_height() - 80.0, 30.0, RED);
+        } else {
+            self.notices.draw(20.0, screen_height() - 80.0);
+        }
+        draw_text(
+            &format!(
+                "Press {} to restart, {} to return to menu, {} to pause.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+                self.bindings.label(Action::Pause),
+            ),
+            20.0,
+            screen_height() - 20.0,
+            20.0,
+            DARKGRAY,
+        );
+
+        if round.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, true);
+        }
+    }
+}
+
+impl Round {
+    fn elapsed(&self) -> f64 {
+        self.clock.elapsed().min(ROUND_TIME)
+    }
+
+    fn toggle_pause(&mut self) {
+        if let Some(paused_for) = self.clock.toggle_pause() {
+            for (mole, next_toggle) in self.holes.iter_mut().zip(&mut self.next_toggle) {
+                mole.delay(paused_for);
+                *next_toggle += paused_for;
+            }
+        }
+    }
+
+    // Pop moles up with a number, and back down when their time is up
+    fn update_moles(&mut self) {
+        let now = get_time();
+        for i in 0..self.holes.len() {
+            if now < self.next_toggle[i] {
+                continue;
+            }
+            if self.numbers[i].take().is_some() {
+                self.holes[i].hide();
+                self.next_toggle[i] = now + self.rng.gen_range(DOWN_TIME.0, DOWN_TIME.1);
+            } else {
+                // Make sure the answer is always somewhere on the board
+                let answer_shown = self.numbers.contains(&Some(self.question.answer));
+                let number = if !answer_shown || self.rng.gen_range(0.0, 1.0) < ANSWER_CHANCE {
+                    self.question.answer
+                } else {
+                    distractor(self.question.answer, &self.rng)
+                };
+                self.numbers[i] = Some(number);
+                self.holes[i].show();
+                let (low, high) = UP_TIME[self.difficulty];
+                self.next_toggle[i] = now + self.rng.gen_range(low, high);
+            }
+        }
+    }
+
+    fn whack(&mut self, hole: usize, popups: &mut Popups, notices: &mut Notices) {
+        let Some(number) = self.numbers[hole] else {
+            return;
+        };
+        let (x, y) = self.holes[hole].position().into();
+        if number != self.question.answer {
+            self.score -= WRONG_PENALTY;
+            popups.spawn(&format!("-{}", WRONG_PENALTY), x, y - 100.0, RED);
+            notices.push(&format!("{} = {}, not {}", self.question.text, self.question.answer, number), RED);
+            self.numbers[hole] = None;
+            self.holes[hole].hide();
+            self.next_toggle[hole] = get_time() + self.rng.gen_range(DOWN_TIME.0, DOWN_TIME.1);
+            return;
+        }
+
+        self.score += RIGHT_POINTS;
+        popups.spawn(&format!("+{}", RIGHT_POINTS), x, y - 100.0, GREEN);
+        // A new question makes every number on the board stale, clear them all
+        self.question = question(self.difficulty, &self.rng);
+        let now = get_time();
+        for i in 0..self.holes.len() {
+            if self.numbers[i].take().is_some() {
+                self.holes[i].hide();
+                self.next_toggle[i] = now + self.rng.gen_range(DOWN_TIME.0, DOWN_TIME.1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questions_come_with_their_answers() {
+        let rng = RandGenerator::new();
+        rng.srand(7);
+        for difficulty in 0..DIFFICULTIES.len() {
+            for _ in 0..200 {
+                let question = question(difficulty, &rng);
+                let parts: Vec<&str> = question.text.split(' ').collect();
+                let (a, b): (i32, i32) = (parts[0].parse().unwrap(), parts[2].parse().unwrap());
+                let answer = match parts[1] {
+                    "+" => a + b,
+                    "-" => a - b,
+                    "×" => a * b,
+                    "÷" => {
+                        assert_eq!(a % b, 0, "{:?} doesn't divide evenly", question);
+                        a / b
+                    }
+                    op => panic!("unknown operator {}", op),
+                };
+                assert_eq!(answer, question.answer);
+                assert!(answer >= 0);
+                assert_ne!(distractor(answer, &rng), answer);
+            }
+        }
+    }
+}
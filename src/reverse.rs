@@ -0,0 +1,311 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings, Input};
+use crate::clock::Clock;
+use crate::game1::Mole;
+use crate::gamepad::{self, Button};
+use crate::hole;
+use crate::hud::{self, Popups};
+use crate::input::{self, PressKind};
+use crate::keyboard;
+use crate::layout;
+use crate::menu::{draw_options, option_buttons, pick_option};
+use crate::settings::Settings;
+
+const ROUND_TIME: f64 = 60.0;
+const LIVES: u32 = 3;
+const POINTS_PER_SECOND: f32 = 3.0;
+const BOARD_SCALE: f32 = 0.7;   // 3x3 holes need smaller moles than the 2x2 modes
+const TUNNEL_TIME: f64 = 0.2;   // Underground travel between holes
+const SWING_TIME: f64 = 0.12;   // How long the hammer shows its strike
+const RECOVER_TIME: f64 = 0.4;  // Pause after a swing before the hammer reacts again
+
+// How the AI hammer plays at each difficulty: seconds before it reacts to a mole,
+// how fast it travels (pixels per second) and how far off its aim can be (pixels)
+const REACTION_TIME: [f64; 3] = [0.9, 0.6, 0.35];
+const HAMMER_SPEED: [f32; 3] = [900.0, 1300.0, 1800.0];
+const AIM_ERROR: [f32; 3] = [50.0, 30.0, 15.0];
+
+const DIFFICULTIES: [(&str, Color); 3] = [("Easy", GREEN), ("Medium", ORANGE), ("Hard", RED)];
+
+// Where the AI hammer is heading and when it may start moving
+struct Target {
+    hole: usize,
+    aim: Vec2,
+    move_at: f64,
+}
+
+struct Round {
+    difficulty: usize, // Index into the difficulty tables
+    holes: Vec<Mole>,  // Only the player's hole is ever visible
+    player: Option<usize>,
+    tunneling: Option<(usize, f64)>, // Hole the player is heading to, and when they get there
+    hammer: Vec2,
+    target: Option<Target>,
+    swung_at: f64,
+    score: f32,
+    lives: u32,
+    clock: Clock,
+}
+
+// Reverse mode: you are the mole. Pop up out of the 3x3 holes to collect points while an
+// AI hammer hunts you, using the same hit test as a player's click on a mole.
+pub struct Reverse {
+    assets: Arc<Assets>,
+    bindings: Bindings,
+    hit_leniency: f32,
+    scale: f32,
+    positions: Vec<(f32, f32)>,
+    hole_keys: Vec<Input>,
+    round: Option<Round>, // None while picking a difficulty
+    popups: Popups,
+}
+
+impl Reverse {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let scale = settings.easy_scale * BOARD_SCALE;
+        Self {
+            assets,
+            bindings: bindings.clone(),
+            hit_leniency: settings.hit_leniency,
+            scale,
+            positions: layout::grid(3, 3, scale),
+            hole_keys: keyboard::hole_inputs(3, 3, bindings),
+            round: None,
+            popups: Popups::new(),
+        }
+    }
+
+    fn start(&mut self, difficulty: usize) {
+        self.round = Some(Round {
+            difficulty,
+            holes: self.positions.iter().map(|&(x, y)| Mole::new(x, y)).collect(),
+            player: None,
+            tunneling: None,
+            hammer: vec2(screen_width() / 2.0, 60.0),
+            target: None,
+            swung_at: f64::NEG_INFINITY,
+            score: 0.0,
+            lives: LIVES,
+            clock: Clock::start(),
+        });
+        self.popups = Popups::new();
+    }
+
+    // Hole whose mound is under the point, if any
+    fn hole_at(&self, point: Vec2) -> Option<usize> {
+        let radius = 128.0 * self.scale;
+        self.positions
+            .iter()
+            .position(|&(x, y)| vec2(x, y).distance(point) < radius)
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+
+        let Some(round) = &mut self.round else {
+            if let Some(difficulty) = pick_option(&option_buttons(DIFFICULTIES.len())) {
+                self.start(difficulty);
+            }
+            return back_to_menu;
+        };
+
+        if self.bindings.pressed(Action::Restart) {
+            let difficulty = round.difficulty;
+            self.start(difficulty);
+            return back_to_menu;
+        }
+        if self.bindings.pressed(Action::Pause) || gamepad::is_button_pressed(Button::Start) {
+            round.toggle_pause();
+        }
+        if !round.clock.is_running() {
+            return back_to_menu;
+        }
+
+        // Pick a hole with its key or by clicking it. Picking the hole you are in ducks.
+        let mut picked = keyboard::pressed_hole(&self.hole_keys);
+        for press in input::presses() {
+            if press.kind == PressKind::Primary {
+                picked = self.hole_at(vec2(press.x, press.y)).or(picked);
+            }
+        }
+        let round = self.round.as_mut().expect("round is running");
+        if let Some(hole) = picked {
+            round.pick(hole);
+        }
+
+        round.update_player(&mut self.popups);
+        round.update_hammer(&self.assets, self.scale, self.hit_leniency, &mut self.popups);
+        self.popups.update();
+
+        if round.elapsed() >= ROUND_TIME || round.lives == 0 {
+            round.clock.end();
+            if let Some(hole) = round.player.take() {
+                round.holes[hole].hide();
+            }
+        }
+
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        let Some(round) = &self.round else {
+            draw_text("BE THE MOLE", 60.0, 100.0, 60.0, BLACK);
+            draw_text("Pop out of the holes to collect points, duck before the hammer gets you.", 60.0, 160.0, 28.0, DARKGRAY);
+            draw_text("Pick how quick the hammer is (1 / 2 / 3):", 60.0, 210.0, 28.0, DARKGRAY);
+            draw_options(&DIFFICULTIES);
+            return;
+        };
+
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
+        let mut holes: Vec<&Mole> = round.holes.iter().collect();
+        holes.sort_by(|a, b| a.position().y.total_cmp(&b.position().y));
+        for mole in holes {
+            mole.draw(&self.assets, self.scale);
+        }
+        for (&(x, y), &input) in self.positions.iter().zip(&self.hole_keys) {
+            hole::draw_label(&self.assets.mole, vec2(x, y), self.scale, &keyboard::key_label(input));
+        }
+
+        let swinging = get_time() - round.swung_at < SWING_TIME;
+        hud::draw_hammer(round.hammer, if swinging { RED } else { GRAY });
+        self.popups.draw();
+
+        draw_text(&format!("Score: {}", round.score as i32), 20.0, 40.0, 40.0, WHITE);
+        draw_text(&format!("Time: {:.0}", (ROUND_TIME - round.elapsed()).max(0.0)), 20.0, 80.0, 40.0, WHITE);
+        draw_text(&format!("Lives: {}", round.lives), 20.0, 120.0, 40.0, WHITE);
+
+        if round.clock.is_over() {
+            let result = if round.lives == 0 { "Bonked out!" } else { "Time's up, you survived!" };
+            draw_text(
+                &format!("{} Final Score: {}", result, round.score as i32),
+                20.0,
+                screen_height() - 80.0,
+                30.0,
+                RED,
+            );
+        }
+        draw_text(
+            &format!(
+                "Click a hole or press its key to pop up there, again to duck. {} restarts, {} returns to menu.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+            ),
+            20.0,
+            screen_height() - 20.0,
+            20.0,
+            DARKGRAY,
+        );
+
+        if round.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, true);
+        }
+    }
+}
+
+impl Round {
+    fn elapsed(&self) -> f64 {
+        self.clock.elapsed().min(ROUND_TIME)
+    }
+
+    fn toggle_pause(&mut self) {
+        let Some(paused_for) = self.clock.toggle_pause() else {
+            return;
+        };
+        self.swung_at += paused_for;
+        for hole in &mut self.holes {
+            hole.delay(paused_for);
+        }
+        if let Some((_, arrive_at)) = &mut self.tunneling {
+            *arrive_at += paused_for;
+        }
+        if let Some(target) = &mut self.target {
+            target.move_at += paused_for;
+        }
+    }
+
+    // Duck in the current hole, or tunnel over to another one
+    fn pick(&mut self, hole: usize) {
+        if let Some(current) = self.player.take() {
+            self.holes[current].hide();
+            if current == hole {
+                return;
+            }
+        }
+        self.tunneling = Some((hole, get_time() + TUNNEL_TIME));
+    }
+
+    fn update_player(&mut self, popups: &mut Popups) {
+        if let Some((hole, arrive_at)) = self.tunneling {
+            if get_time() >= arrive_at {
+                self.tunneling = None;
+                self.player = Some(hole);
+                self.holes[hole].show();
+            }
+        }
+
+        // Points only come in while fully out of the hole
+        if let Some(hole) = self.player {
+            if self.holes[hole].is_up() {
+                let before = self.score as i32;
+                self.score += POINTS_PER_SECOND * get_frame_time();
+                if self.score as i32 > before {
+                    let (x, y) = self.holes[hole].position().into();
+                    popups.spawn("+1", x, y - 100.0, GREEN);
+                }
+            }
+        }
+    }
+
+    fn update_hammer(&mut self, assets: &Assets, scale: f32, leniency: f32, popups: &mut Popups) {
+        let now = get_time();
+        if now - self.swung_at < RECOVER_TIME {
+            return;
+        }
+
+        // Notice the mole where it is now, after the reaction time
+        match (self.player, &self.target) {
+            (Some(hole), Some(target)) if target.hole == hole => {}
+            (Some(hole), _) => {
+                let error = AIM_ERROR[self.difficulty];
+                let body = self.holes[hole].position() - vec2(0.0, assets.mole.texture.height() * scale * 0.1);
+                self.target = Some(Target {
+                    hole,
+                    aim: body + vec2(rand::gen_range(-error, error), rand::gen_range(-error, error)),
+                    move_at: now + REACTION_TIME[self.difficulty],
+                });
+            }
+            (None, _) => self.target = None,
+        }
+        let Some(target) = &self.target else {
+            return;
+        };
+        if now < target.move_at {
+            return;
+        }
+
+        // Close in, then strike with the same hit test a click on the mole uses
+        let to_aim = target.aim - self.hammer;
+        let step = HAMMER_SPEED[self.difficulty] * get_frame_time();
+        if to_aim.length() > step {
+            self.hammer += to_aim.normalize() * step;
+            return;
+        }
+        self.hammer = target.aim;
+        self.swung_at = now;
+        let hole = target.hole;
+        self.target = None;
+        let mole = &mut self.holes[hole];
+        if mole.is_clicked(self.hammer.x, self.hammer.y, &assets.mole, scale, leniency) {
+            mole.hide();
+            self.player = None;
+            self.lives = self.lives.saturating_sub(1);
+            popups.spawn("Bonk!", self.hammer.x, self.hammer.y, RED);
+        } else {
+            popups.spawn("Whiff", self.hammer.x, self.hammer.y, LIGHTGRAY);
+        }
+    }
+}
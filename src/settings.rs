@@ -0,0 +1,150 @@
+use std::fs;
+
+const SETTINGS_PATH: &str = "settings.txt";
+const MIN_SCALE: f32 = 0.25;
+const MAX_SCALE: f32 = 3.0;
+pub const MIN_SPAWN_RATE: f32 = 0.25;
+pub const MAX_SPAWN_RATE: f32 = 4.0;
+const MAX_FRENZY_SECONDS: f32 = 30.0;
+
+// Player settings, read from `settings.txt` as `key = value` lines.
+// Missing files, unknown keys and bad values fall back to the defaults.
+#[derive(Clone)]
+pub struct Settings {
+    pub hit_leniency: f32, // Extra radius (pixels) around a sprite's opaque area that still counts as a hit
+    pub easy_scale: f32,   // Sprite and hole spacing scale for Easy mode
+    pub medium_scale: f32, // Sprite and hole spacing scale for Medium mode
+    pub keyboard_mode: bool, // Hit holes with keys and show the key under each hole
+    pub zen_spawn_rate: f32, // How quickly moles come up in Zen mode, 1.0 is Medium speed
+    pub frenzy_seconds: f32, // Length of the frenzy at the end of a timed Medium round, 0 turns it off
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            hit_leniency: 0.0,
+            easy_scale: 1.0,
+            medium_scale: 1.0,
+            keyboard_mode: false,
+            zen_spawn_rate: 1.0,
+            frenzy_seconds: 10.0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        if let Ok(text) = fs::read_to_string(SETTINGS_PATH) {
+            for line in text.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if let Some((key, value)) = line.split_once('=') {
+                    settings.set(key.trim(), value.trim());
+                }
+            }
+        }
+        settings
+    }
+
+    // Rewrite the `key = value` lines for our settings in place, keeping comments,
+    // unknown keys and the order of the file. Keys not in the file yet go at the end.
+    pub fn save(&self) {
+        let text = fs::read_to_string(SETTINGS_PATH).unwrap_or_default();
+        if let Err(err) = fs::write(SETTINGS_PATH, self.merge_into(&text)) {
+            eprintln!("Failed to save {}: {}", SETTINGS_PATH, err);
+        }
+    }
+
+    // Every key `set` understands, with its current value
+    fn values(&self) -> [(&'static str, String); 6] {
+        [
+            ("hit_leniency", self.hit_leniency.to_string()),
+            ("easy_scale", self.easy_scale.to_string()),
+            ("medium_scale", self.medium_scale.to_string()),
+            ("keyboard_mode", self.keyboard_mode.to_string()),
+            ("zen_spawn_rate", self.zen_spawn_rate.to_string()),
+            ("frenzy_seconds", self.frenzy_seconds.to_string()),
+        ]
+    }
+
+    fn merge_into(&self, text: &str) -> String {
+        let values = self.values();
+        let mut written = vec![false; values.len()];
+        let mut out = String::new();
+        for line in text.lines() {
+            let key = line.split_once('=').map(|(key, _)| key.trim());
+            match values.iter().position(|(name, _)| Some(*name) == key) {
+                // Each key is written once, a repeat would override it on the next load
+                Some(i) => {
+                    if !written[i] {
+                        out.push_str(&format!("{} = {}\n", values[i].0, values[i].1));
+                        written[i] = true;
+                    }
+                }
+                None => {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+        for ((key, value), _) in values.iter().zip(written).filter(|&(_, written)| !written) {
+            out.push_str(&format!("{} = {}\n", key, value));
+        }
+        out
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "hit_leniency" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.hit_leniency = v.max(0.0);
+                }
+            }
+            "easy_scale" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.easy_scale = v.clamp(MIN_SCALE, MAX_SCALE);
+                }
+            }
+            "medium_scale" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.medium_scale = v.clamp(MIN_SCALE, MAX_SCALE);
+                }
+            }
+            "keyboard_mode" => {
+                if let Ok(v) = value.parse::<bool>() {
+                    self.keyboard_mode = v;
+                }
+            }
+            "zen_spawn_rate" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.zen_spawn_rate = v.clamp(MIN_SPAWN_RATE, MAX_SPAWN_RATE);
+                }
+            }
+            "frenzy_seconds" => {
+                if let Ok(v) = value.parse::<f32>() {
+                    self.frenzy_seconds = v.clamp(0.0, MAX_FRENZY_SECONDS);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_keeps_comments_unknown_keys_and_order() {
+        let settings = Settings { easy_scale: 1.5, ..Settings::default() };
+        let text = "# My settings\n# easy_scale = 3\nmedium_scale = 2\ncolour = blue\neasy_scale = 0.5\n";
+        let saved = settings.merge_into(text);
+        let lines: Vec<&str> = saved.lines().collect();
+        assert_eq!(&lines[..5], ["# My settings", "# easy_scale = 3", "medium_scale = 1", "colour = blue", "easy_scale = 1.5"]);
+        assert_eq!(lines[5], "hit_leniency = 0");
+        assert_eq!(lines.len(), 5 + 4);
+    }
+}
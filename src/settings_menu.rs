@@ -0,0 +1,159 @@
+use macroquad::prelude::*;
+
+use crate::bindings::{Action, Bindings, Input, BINDABLE_KEYS};
+use crate::gamepad::{self, Button};
+use crate::menu::draw_button;
+use crate::settings::Settings;
+
+const ROW_HEIGHT: f32 = 34.0;
+const ROW_WIDTH: f32 = 520.0; // At most, narrower when the window is
+const ROWS_TOP: f32 = 140.0;
+const SIDE_MARGIN: f32 = 30.0;
+const GAP: f32 = 20.0;
+const BUTTON_WIDTH: f32 = 360.0; // At most, like the rows
+const BUTTON_HEIGHT: f32 = 60.0;
+const FOOTER_HEIGHT: f32 = 120.0; // Buttons and the conflict message under the rows
+
+// Rows in each column of actions, the columns balanced and fitting a `height` window
+fn rows_per_column(count: usize, height: f32) -> usize {
+    let fit = ((height - ROWS_TOP - FOOTER_HEIGHT) / ROW_HEIGHT).floor().max(1.0) as usize;
+    count.div_ceil(count.div_ceil(fit))
+}
+
+// Settings screen: rebind controls and toggle keyboard mode.
+// Click an action, then press the key or mouse button it should use, or Cancel.
+pub struct SettingsMenu {
+    rebinding: Option<Action>,
+    conflict: Option<String>, // Why the last input pressed while rebinding was refused
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        Self { rebinding: None, conflict: None }
+    }
+
+    // Waiting for the key or mouse button to bind, so presses shouldn't do anything else
+    pub fn is_rebinding(&self) -> bool {
+        self.rebinding.is_some()
+    }
+
+    // Rows fill columns top to bottom, as many columns as the window height needs
+    fn row_rect(index: usize, count: usize, width: f32, height: f32) -> Rect {
+        let rows = rows_per_column(count, height);
+        let columns = count.div_ceil(rows);
+        let row_width = ROW_WIDTH.min((width - 2.0 * SIDE_MARGIN - (columns - 1) as f32 * GAP) / columns as f32);
+        let left = (width - (columns as f32 * (row_width + GAP) - GAP)) / 2.0;
+        let (col, row) = (index / rows, index % rows);
+        Rect::new(left + col as f32 * (row_width + GAP), ROWS_TOP + row as f32 * ROW_HEIGHT, row_width, ROW_HEIGHT - 4.0)
+    }
+
+    // Keyboard mode, Reset controls and Back in a row under the action rows
+    fn button_rects(count: usize, width: f32, height: f32) -> [Rect; 3] {
+        let top = ROWS_TOP + rows_per_column(count, height) as f32 * ROW_HEIGHT + GAP;
+        let button_width = BUTTON_WIDTH.min((width - 2.0 * SIDE_MARGIN - 2.0 * GAP) / 3.0);
+        let left = (width - (3.0 * button_width + 2.0 * GAP)) / 2.0;
+        [0, 1, 2].map(|i| Rect::new(left + i as f32 * (button_width + GAP), top, button_width, BUTTON_HEIGHT))
+    }
+
+    // Draw the screen. Returns true when the player wants to go back to the menu.
+    pub fn draw(&mut self, settings: &mut Settings, bindings: &mut Bindings) -> bool {
+        draw_text("SETTINGS", 60.0, 80.0, 60.0, BLACK);
+        draw_text("Click an action, then press the key or mouse button to use for it.", 60.0, 115.0, 24.0, DARKGRAY);
+
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse = vec2(mouse_x, mouse_y);
+        let count = bindings.actions().len();
+        let row = |i| Self::row_rect(i, count, screen_width(), screen_height());
+        for (i, &(action, input)) in bindings.actions().iter().enumerate() {
+            let rect = row(i);
+            let waiting = self.rebinding == Some(action);
+            let hovered = rect.contains(mouse);
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, if waiting { GOLD } else if hovered { WHITE } else { Color::new(1.0, 1.0, 1.0, 0.5) });
+            draw_text(&action.description(), rect.x + 10.0, rect.y + 22.0, 24.0, BLACK);
+            let shown = if waiting { "press a key...".to_string() } else { input.name() };
+            let shown_width = measure_text(&shown, None, 24, 1.0).width;
+            draw_text(&shown, rect.right() - 10.0 - shown_width, rect.y + 22.0, 24.0, DARKBLUE);
+        }
+
+        let buttons = Self::button_rects(count, screen_width(), screen_height());
+        let keyboard_mode = if settings.keyboard_mode { "ON" } else { "OFF" };
+        let labels = [
+            format!("Keyboard mode: {}", keyboard_mode),
+            "Reset controls".to_string(),
+            if self.rebinding.is_some() { "Cancel" } else { "Back" }.to_string(),
+        ];
+        for (rect, label) in buttons.iter().zip(&labels) {
+            draw_button(rect.x, rect.y, rect.w, rect.h, label, GRAY);
+        }
+        let clicked = |rect: &Rect| is_mouse_button_pressed(MouseButton::Left) && rect.contains(mouse);
+
+        // Waiting for the new input: the next key or mouse button press is it
+        if let Some(action) = self.rebinding {
+            // Cancel keeps the current input, a click anywhere else may be the new one
+            if clicked(&buttons[2]) || gamepad::is_button_pressed(Button::B) {
+                self.rebinding = None;
+                self.conflict = None;
+                return false;
+            }
+            let pressed_key = get_last_key_pressed().filter(|key| BINDABLE_KEYS.contains(key));
+            let pressed_button = [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+                .into_iter()
+                .find(|&button| is_mouse_button_pressed(button));
+            let input = pressed_key.map(Input::Key).or(pressed_button.map(Input::Mouse));
+            // Refuse inputs another action needs at the same time, and keep waiting
+            match input.map(|input| (input, bindings.conflict(action, input))) {
+                Some((input, Some(other))) => {
+                    self.conflict = Some(format!("{} is already used for {}, press another.", input.name(), other.description()));
+                }
+                Some((input, None)) => {
+                    bindings.set(action, input);
+                    bindings.save();
+                    self.rebinding = None;
+                    self.conflict = None;
+                }
+                None => {}
+            }
+            if let Some(conflict) = &self.conflict {
+                draw_text(conflict, buttons[0].x, buttons[0].bottom() + 32.0, 24.0, RED);
+            }
+            return false;
+        }
+
+        if let Some(i) = (0..count).find(|&i| clicked(&row(i))) {
+            self.rebinding = Some(bindings.actions()[i].0);
+            return false;
+        }
+        if clicked(&buttons[0]) {
+            settings.keyboard_mode = !settings.keyboard_mode;
+            settings.save();
+        } else if clicked(&buttons[1]) {
+            bindings.reset();
+            bindings.save();
+        } else if clicked(&buttons[2]) {
+            return true;
+        }
+
+        bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::B)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_screen_fits_the_default_window() {
+        let count = Bindings::load().actions().len();
+        let (width, height) = (800.0, 600.0);
+        for i in 0..count {
+            let rect = SettingsMenu::row_rect(i, count, width, height);
+            assert!(rect.x >= 0.0 && rect.right() <= width, "row {} is off screen: {:?}", i, rect);
+        }
+        let buttons = SettingsMenu::button_rects(count, width, height);
+        assert!(buttons[0].x >= 0.0 && buttons[2].right() <= width);
+        // Room for the conflict message under the buttons
+        assert!(buttons[0].bottom() + 40.0 <= height);
+        let last_row = SettingsMenu::row_rect(count - 1, count, width, height);
+        assert!(last_row.bottom() < buttons[0].y);
+    }
+}
@@ -0,0 +1,137 @@
+use macroquad::prelude::*;
+
+// Pixels with alpha at or below this count as transparent for hit tests
+const ALPHA_THRESHOLD: u8 = 32;
+
+pub struct Sprite {
+    pub texture: Texture2D,
+    pub mask: HitMask,
+}
+
+impl Sprite {
+    pub async fn load(path: &str) -> Self {
+        let image = load_image(path)
+            .await
+            .unwrap_or_else(|_| panic!("Failed to load {}", path));
+        Self::from_image(&image)
+    }
+
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            texture: Texture2D::from_image(image),
+            mask: HitMask::from_image(image),
+        }
+    }
+
+    // Screen rectangle covered by the sprite when drawn centered on (x, y)
+    pub fn rect(&self, x: f32, y: f32, scale: f32) -> Rect {
+        let width = self.texture.width() * scale;
+        let height = self.texture.height() * scale;
+        Rect::new(x - width / 2.0, y - height / 2.0, width, height)
+    }
+
+    // Draw only the top `visible_height` screen pixels of the sprite placed over `rect`
+    pub fn draw_top(&self, rect: Rect, visible_height: f32) {
+        let visible_height = visible_height.min(rect.h);
+        if visible_height <= 0.0 {
+            return;
+        }
+        let source_height = self.texture.height() * visible_height / rect.h;
+        draw_texture_ex(
+            &self.texture,
+            rect.x,
+            rect.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(rect.w, visible_height)),
+                source: Some(Rect::new(0.0, 0.0, self.texture.width(), source_height)),
+                ..Default::default()
+            },
+        );
+    }
+
+    // Is the screen point (x, y) on an opaque pixel of this sprite drawn over `rect`?
+    // `leniency` is a radius in screen pixels around the opaque area that still counts as a hit.
+    pub fn contains(&self, rect: Rect, x: f32, y: f32, leniency: f32) -> bool {
+        if rect.w <= 0.0 || rect.h <= 0.0 {
+            return false;
+        }
+        let scale = self.mask.width as f32 / rect.w;
+        let u = (x - rect.x) * scale;
+        let v = (y - rect.y) * self.mask.height as f32 / rect.h;
+        self.mask.distance_at(u, v) <= leniency * scale
+    }
+}
+
+// Precomputed from the sprite's alpha channel: for every texture pixel, the distance
+// to the nearest opaque pixel (0 on opaque pixels), so any leniency radius is a single lookup.
+pub struct HitMask {
+    width: usize,
+    height: usize,
+    distance: Vec<f32>,
+}
+
+impl HitMask {
+    pub fn from_image(image: &Image) -> Self {
+        let width = image.width as usize;
+        let height = image.height as usize;
+        let mut distance: Vec<f32> = image
+            .bytes
+            .chunks_exact(4)
+            .map(|pixel| if pixel[3] > ALPHA_THRESHOLD { 0.0 } else { f32::INFINITY })
+            .collect();
+
+        // Two-pass chamfer distance transform
+        let diagonal = std::f32::consts::SQRT_2;
+        for y in 0..height {
+            for x in 0..width {
+                let mut d = distance[y * width + x];
+                if x > 0 {
+                    d = d.min(distance[y * width + x - 1] + 1.0);
+                }
+                if y > 0 {
+                    d = d.min(distance[(y - 1) * width + x] + 1.0);
+                    if x > 0 {
+                        d = d.min(distance[(y - 1) * width + x - 1] + diagonal);
+                    }
+                    if x + 1 < width {
+                        d = d.min(distance[(y - 1) * width + x + 1] + diagonal);
+                    }
+                }
+                distance[y * width + x] = d;
+            }
+        }
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                let mut d = distance[y * width + x];
+                if x + 1 < width {
+                    d = d.min(distance[y * width + x + 1] + 1.0);
+                }
+                if y + 1 < height {
+                    d = d.min(distance[(y + 1) * width + x] + 1.0);
+                    if x + 1 < width {
+                        d = d.min(distance[(y + 1) * width + x + 1] + diagonal);
+                    }
+                    if x > 0 {
+                        d = d.min(distance[(y + 1) * width + x - 1] + diagonal);
+                    }
+                }
+                distance[y * width + x] = d;
+            }
+        }
+
+        Self { width, height, distance }
+    }
+
+    // Distance in texture pixels from (u, v) to the nearest opaque pixel.
+    // Points outside the texture are measured from the closest edge pixel.
+    pub fn distance_at(&self, u: f32, v: f32) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return f32::INFINITY;
+        }
+        let cu = u.clamp(0.0, self.width as f32 - 1.0);
+        let cv = v.clamp(0.0, self.height as f32 - 1.0);
+        let outside = vec2(u - cu, v - cv).length();
+        outside + self.distance[cv as usize * self.width + cu as usize]
+    }
+}
@@ -0,0 +1,82 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::Game;
+use crate::highscores::{HighScores, Run};
+use crate::settings::Settings;
+
+const LIVES: u32 = 3;
+const MODE: &str = "survival";
+
+// Endless survival on the Medium rules: no clock, three lives, ever faster moles.
+// Finished runs go into their own high-score tables, by time survived and by score.
+pub struct Survival {
+    game: Game,
+    bindings: Bindings,
+    highscores: HighScores,
+    recorded: Option<Run>, // This run, once it's over and saved
+}
+
+impl Survival {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        Self {
+            game: Game::new(2, assets, settings, bindings).with_lives(LIVES),
+            bindings: bindings.clone(),
+            highscores: HighScores::load(),
+            recorded: None,
+        }
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.game.update();
+        if self.game.is_over() && self.recorded.is_none() {
+            let run = Run { score: self.game.score(), seconds: self.game.elapsed() };
+            self.highscores.record(MODE, run);
+            self.highscores.save();
+            self.recorded = Some(run);
+        }
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        self.game.draw();
+        let Some(this_run) = self.recorded else {
+            return;
+        };
+
+        draw_rectangle(140.0, 100.0, 900.0, 500.0, Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_text(&format!("You survived {:.1}s", this_run.seconds), 180.0, 160.0, 50.0, GOLD);
+        let tables = [
+            ("Longest survival", self.highscores.best_times(MODE, false), 180.0),
+            ("Best score", self.highscores.best_scores(MODE), 620.0),
+        ];
+        for (title, runs, x) in tables {
+            draw_text(title, x, 220.0, 32.0, WHITE);
+            for (i, run) in runs.iter().enumerate() {
+                // Highlight this run in the tables
+                let color = if *run == this_run { GOLD } else { WHITE };
+                draw_text(
+                    &format!("{:2}. {:6.1}s  {:4} pts", i + 1, run.seconds, run.score),
+                    x,
+                    260.0 + i as f32 * 30.0,
+                    28.0,
+                    color,
+                );
+            }
+        }
+        draw_text(
+            &format!(
+                "Press {} to try again or {} to return to menu.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+            ),
+            180.0,
+            580.0,
+            26.0,
+            LIGHTGRAY,
+        );
+    }
+}
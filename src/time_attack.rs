@@ -0,0 +1,95 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::Game;
+use crate::highscores::{HighScores, Run};
+use crate::settings::Settings;
+
+const TARGET: i32 = 50;
+const SPLIT_EVERY: i32 = 10;
+const MODE: &str = "time_attack";
+
+// Race to TARGET points on the Medium rules, the result is the time it took.
+// A split time is taken the first time the score reaches each multiple of SPLIT_EVERY.
+pub struct TimeAttack {
+    game: Game,
+    bindings: Bindings,
+    highscores: HighScores,
+    splits: Vec<f64>,       // Seconds at SPLIT_EVERY, 2 * SPLIT_EVERY, ... points
+    recorded: Option<Run>,  // This run, once it's over and saved
+}
+
+impl TimeAttack {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        Self {
+            game: Game::new(2, assets, settings, bindings).with_target(TARGET),
+            bindings: bindings.clone(),
+            highscores: HighScores::load(),
+            splits: Vec::new(),
+            recorded: None,
+        }
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let back_to_menu = self.game.update();
+
+        // A cat can knock the score back down, a split only counts the first time
+        let next_split = (self.splits.len() as i32 + 1) * SPLIT_EVERY;
+        if self.game.score() >= next_split {
+            self.splits.push(self.game.elapsed());
+        }
+
+        if self.game.is_over() && self.recorded.is_none() {
+            let run = Run { score: self.game.score(), seconds: self.game.elapsed() };
+            self.highscores.record(MODE, run);
+            self.highscores.save();
+            self.recorded = Some(run);
+        }
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        self.game.draw();
+
+        // Splits down the right side, with the time each block of points took
+        let x = screen_width() - 260.0;
+        draw_text("Splits", x, 40.0, 32.0, WHITE);
+        let mut previous = 0.0;
+        for (i, &split) in self.splits.iter().enumerate() {
+            draw_text(
+                &format!("{:3} pts {:6.1}s (+{:.1})", (i as i32 + 1) * SPLIT_EVERY, split, split - previous),
+                x,
+                75.0 + i as f32 * 28.0,
+                24.0,
+                WHITE,
+            );
+            previous = split;
+        }
+
+        let Some(this_run) = self.recorded else {
+            return;
+        };
+        draw_rectangle(140.0, 100.0, 900.0, 500.0, Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_text(&format!("{} points in {:.1}s", TARGET, this_run.seconds), 180.0, 160.0, 50.0, GOLD);
+        draw_text("Fastest times", 180.0, 220.0, 32.0, WHITE);
+        for (i, run) in self.highscores.best_times(MODE, true).iter().enumerate() {
+            // Highlight this run in the table
+            let color = if *run == this_run { GOLD } else { WHITE };
+            draw_text(&format!("{:2}. {:6.1}s", i + 1, run.seconds), 180.0, 260.0 + i as f32 * 30.0, 28.0, color);
+        }
+        draw_text(
+            &format!(
+                "Press {} to try again or {} to return to menu.",
+                self.bindings.label(Action::Restart),
+                self.bindings.label(Action::Back),
+            ),
+            180.0,
+            580.0,
+            26.0,
+            LIGHTGRAY,
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::clock::Clock;
+use crate::game2::{self, Mole, Spawner};
+use crate::gamepad::{self, Button};
+use crate::hud::{self, Notices, Popups};
+use crate::layout;
+use crate::menu::{draw_options, option_buttons, pick_option};
+use crate::settings::Settings;
+
+const ROUND_TIME: f64 = 60.0;
+const BOARD_SCALE: f32 = 0.7;
+const CAT_PENALTY: i32 = 5;
+
+const LETTERS: &[&str] = &[
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m",
+    "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z",
+];
+const SHORT_WORDS: &[&str] = &[
+    "dig", "mole", "hole", "dirt", "grub", "worm", "paw", "nose", "soil", "root",
+    "bug", "hill", "mud", "den", "claw", "seed", "sun", "rain", "log", "leaf",
+];
+const LONG_WORDS: &[&str] = &[
+    "tunnel", "burrow", "hammer", "garden", "meadow", "carrot", "shovel", "pebble",
+    "clover", "beetle", "whisker", "gopher", "harvest", "compost", "sprout", "lantern",
+];
+const LONGER_WORDS: &[&str] = &[
+    "underground", "earthworm", "molehill", "wheelbarrow", "greenhouse", "scarecrow",
+    "vegetable", "hedgehog", "groundhog", "sunflower",
+];
+
+// Word lists by level: moles and cats take words from their level's list,
+// helmet moles from the next one up
+const WORDS: [&[&str]; 4] = [LETTERS, SHORT_WORDS, LONG_WORDS, LONGER_WORDS];
+const LEVELS: [(&str, Color); 3] = [("Letters", GREEN), ("Short words", ORANGE), ("Long words", RED)];
+
+// Words from `list` that neither start nor finish another word already on the board,
+// since typing one would be taken for the other
+fn fitting_words(list: &[&'static str], shown: &[Option<&str>]) -> Vec<&'static str> {
+    list.iter()
+        .copied()
+        .filter(|word| !shown.iter().flatten().any(|shown| shown.starts_with(word) || word.starts_with(shown)))
+        .collect()
+}
+
+struct Round {
+    level: usize,
+    moles: Vec<Mole>,
+    spawners: Vec<Spawner>,
+    words: Vec<Option<&'static str>>, // What each visible mole or cat is wearing
+    typed: String,                    // Letters so far towards one of the words
+    score: i32,
+    clock: Clock,
+    keystrokes: u32,
+    mistakes: u32,     // Letters that matched no word on the board
+    chars_typed: u32,  // Letters of the words that scored, for words per minute
+    words_typed: u32,
+}
+
+// Typing mode: every mole, helmet mole and cat carries a letter or a word, and typing it
+// is the hit. Moles come and go on the Medium spawn cycle; cats still mustn't be hit.
+pub struct Typing {
+    assets: Arc<Assets>,
+    bindings: Bindings,
+    scale: f32,
+    positions: Vec<(f32, f32)>,
+    round: Option<Round>, // None while picking a level
+    popups: Popups,
+    notices: Notices,
+}
+
+impl Typing {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let scale = settings.medium_scale * BOARD_SCALE;
+        Self {
+            assets,
+            bindings: bindings.clone(),
+            scale,
+            positions: layout::grid(3, 3, scale),
+            round: None,
+            popups: Popups::new(),
+            notices: Notices::new(),
+        }
+    }
+
+    fn start(&mut self, level: usize) {
+        let seed = game2::random_seed();
+        self.round = Some(Round {
+            level,
+            moles: self.positions.iter().map(|&(x, y)| Mole::new(x, y)).collect(),
+            spawners: (0..self.positions.len()).map(|i| Spawner::new(seed, i)).collect(),
+            words: vec![None; self.positions.len()],
+            typed: String::new(),
+            score: 0,
+            clock: Clock::start(),
+            keystrokes: 0,
+            mistakes: 0,
+            chars_typed: 0,
+            words_typed: 0,
+        });
+        self.popups = Popups::new();
+        self.notices = Notices::new();
+        // Don't type whatever was pressed to get here
+        clear_input_queue();
+    }
+
+    // Whether `action` is bound to a letter that is being typed right now, so it
+    // doesn't do what it's bound to
+    pub fn captures(&self, action: Action) -> bool {
+        let running = self.round.as_ref().is_some_and(|round| round.clock.is_running());
+        running && self.bindings.get(action).is_letter()
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let pause_key = !self.captures(Action::Pause) && self.bindings.pressed(Action::Pause);
+        let Some(round) = &mut self.round else {
+            if let Some(level) = pick_option(&option_buttons(LEVELS.len())) {
+                self.start(level);
+            }
+            return self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+        };
+
+        if pause_key || gamepad::is_button_pressed(Button::Start) {
+            round.toggle_pause();
+        }
+
+        // Letters and Backspace are for typing during the round, so restarting and
+        // leaving by key only work while paused or once the round is over
+        if !round.clock.is_running() {
+            clear_input_queue();
+            if self.bindings.pressed(Action::Restart) {
+                let level = round.level;
+                self.start(level);
+                return false;
+            }
+            return self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back);
+        }
+
+        round.update_moles();
+        round.type_letters(&mut self.popups, &mut self.notices);
+        self.popups.update();
+        self.notices.update();
+
+        if round.elapsed() >= ROUND_TIME {
+            round.clock.end();
+            game2::hide_all(&mut round.moles, get_time());
+        }
+
+        gamepad::is_button_pressed(Button::Back)
+    }
+
+    pub fn draw(&self) {
+        let Some(round) = &self.round else {
+            draw_text("TYPING", 60.0, 100.0, 60.0, BLACK);
+            draw_text("Type the letter or word on a mole to whack it. Leave the cats alone!", 60.0, 160.0, 28.0, DARKGRAY);
+            draw_text("Pick what the moles carry (1 / 2 / 3):", 60.0, 210.0, 28.0, DARKGRAY);
+            draw_options(&LEVELS);
+            return;
+        };
+
+        draw_texture(&self.assets.background, 0.0, 0.0, WHITE);
+        let mut holes: Vec<(&Mole, &Option<&str>)> = round.moles.iter().zip(&round.words).collect();
+        holes.sort_by(|a, b| a.0.position().1.total_cmp(&b.0.position().1));
+        for (mole, word) in holes {
+            mole.draw(&self.assets, self.scale);
+            if let Some(word) = word {
+                let typed = if word.starts_with(&round.typed) { round.typed.len() } else { 0 };
+                mole.draw_badge(&self.assets, self.scale, word, typed);
+            }
+        }
+        self.popups.draw();
+
+        draw_text(&format!("Score: {}", round.score), 20.0, 40.0, 40.0, WHITE);
+        draw_text(&format!("Time: {:.0}", (ROUND_TIME - round.elapsed()).max(0.0)), 20.0, 80.0, 40.0, WHITE);
+        draw_text(&format!("WPM: {:.0}", round.words_per_minute()), 20.0, 120.0, 40.0, WHITE);
+
+        // What has been typed so far, under the board
+        if !round.clock.is_over() {
+            let text = format!("> {}_", round.typed);
+            let size = measure_text(&text, None, 48, 1.0);
+            draw_text(&text, (screen_width() - size.width) / 2.0, screen_height() - 110.0, 48.0, WHITE);
+            self.notices.draw(20.0, screen_height() - 80.0);
+        } else {
+            draw_rectangle(140.0, 100.0, 900.0, 320.0, Color::new(0.0, 0.0, 0.0, 0.7));
+            draw_text(&format!("Time's up! Final Score: {}", round.score), 180.0, 170.0, 50.0, GOLD);
+            let stats = [
+                format!("Words per minute: {:.0}", round.words_per_minute()),
+                format!("Accuracy: {:.0}%", round.accuracy() * 100.0),
+                format!("Words typed: {}   Mistakes: {}", round.words_typed, round.mistakes),
+            ];
+            for (i, line) in stats.iter().enumerate() {
+                draw_text(line, 180.0, 230.0 + i as f32 * 40.0, 32.0, WHITE);
+            }
+            draw_text(
+                &format!(
+                    "Press {} to play again or {} to return to menu.",
+                    self.bindings.label(Action::Restart),
+                    self.bindings.label(Action::Back),
+                ),
+                180.0,
+                390.0,
+                26.0,
+                LIGHTGRAY,
+            );
+        }
+        draw_text(
+            &format!(
+                "Backspace fixes a typo. Pause ({}) to restart or return to menu.",
+                self.bindings.label(Action::Pause),
+            ),
+            20.0,
+            screen_height() - 20.0,
+            20.0,
+            DARKGRAY,
+        );
+
+        if round.clock.is_paused() {
+            hud::draw_pause_overlay(&self.bindings, true);
+        }
+    }
+}
+
+impl Round {
+    fn elapsed(&self) -> f64 {
+        self.clock.elapsed().min(ROUND_TIME)
+    }
+
+    // Standard words of five letters each, per minute of play
+    fn words_per_minute(&self) -> f64 {
+        let minutes = self.elapsed() / 60.0;
+        if minutes <= 0.0 {
+            return 0.0;
+        }
+        self.chars_typed as f64 / 5.0 / minutes
+    }
+
+    fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 1.0;
+        }
+        (self.keystrokes - self.mistakes) as f64 / self.keystrokes as f64
+    }
+
+    fn toggle_pause(&mut self) {
+        if let Some(paused_for) = self.clock.toggle_pause() {
+            for mole in &mut self.moles {
+                mole.delay(paused_for);
+            }
+        }
+    }
+
+    // A word for a mole that just came up, one that can't be mistaken for a word
+    // already on the board while typing it. None if every word would be.
+    fn pick_word(&self, mole: &Mole) -> Option<&'static str> {
+        let level = match mole {
+            Mole::Helmet_Mole { .. } => (self.level + 1).min(WORDS.len() - 1),
+            _ => self.level,
+        };
+        let words = fitting_words(WORDS[level], &self.words);
+        (!words.is_empty()).then(|| words[rand::gen_range(0, words.len())])
+    }
+
+    // Run the usual spawn cycle, handing out words as moles come up
+    fn update_moles(&mut self) {
+        for i in 0..self.moles.len() {
+            let before = self.moles[i];
+            self.moles[i].update(&mut self.spawners[i]);
+            match (before.is_visible(), self.moles[i].is_visible()) {
+                (false, true) => match self.pick_word(&self.moles[i]) {
+                    Some(word) => self.words[i] = Some(word),
+                    // Nothing can go on it, so the hole stays empty and tries again later
+                    None => {
+                        self.moles[i] = before;
+                        self.spawners[i].next_wait(&before);
+                    }
+                },
+                (true, false) => self.words[i] = None,
+                _ => {}
+            }
+        }
+        // The word being typed may have just gone back down
+        if !self.words.iter().flatten().any(|word| word.starts_with(&self.typed)) {
+            self.typed.clear();
+        }
+    }
+
+    fn type_letters(&mut self, popups: &mut Popups, notices: &mut Notices) {
+        // macroquad hands out queued characters newest first
+        let mut letters = Vec::new();
+        while let Some(c) = get_char_pressed() {
+            letters.push(c);
+        }
+        for c in letters.into_iter().rev().filter(|c| c.is_alphabetic()) {
+            self.keystrokes += 1;
+            let attempt = format!("{}{}", self.typed, c.to_lowercase());
+            if !self.words.iter().flatten().any(|word| word.starts_with(&attempt)) {
+                self.mistakes += 1;
+                self.typed.clear();
+                notices.push(&format!("No \"{}\" on the board", attempt), DARKGRAY);
+                continue;
+            }
+            self.typed = attempt;
+            if let Some(index) = self.words.iter().position(|word| *word == Some(self.typed.as_str())) {
+                self.hit(index, popups, notices);
+                self.typed.clear();
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.typed.pop();
+        }
+    }
+
+    fn hit(&mut self, index: usize, popups: &mut Popups, notices: &mut Notices) {
+        let word = self.words[index].take().unwrap_or_default();
+        let (x, y) = self.moles[index].position();
+        let points = match self.moles[index] {
+            Mole::Normal_Mole { .. } => 1,
+            Mole::Helmet_Mole { .. } => 2,
+            Mole::Cat { .. } => -CAT_PENALTY,
+        };
+        self.score += points;
+        if points > 0 {
+            self.chars_typed += word.chars().count() as u32;
+            self.words_typed += 1;
+            popups.spawn(&format!("+{}", points), x, y - 100.0, if points > 1 { GOLD } else { GREEN });
+        } else {
+            popups.spawn(&points.to_string(), x, y - 100.0, RED);
+            notices.push("Don't type the cats' words!", RED);
+        }
+        let mole = &mut self.moles[index];
+        mole.hide();
+        self.spawners[index].next_wait(mole);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_that_clash_with_the_board_are_left_out() {
+        let shown = [Some("molehill"), None, Some("dig")];
+        let words = fitting_words(SHORT_WORDS, &shown);
+        assert!(!words.contains(&"mole"));
+        assert!(!words.contains(&"dig"));
+        assert!(words.contains(&"hole"));
+        assert!(fitting_words(&["mole", "molehill"], &[Some("mole")]).is_empty());
+    }
+}
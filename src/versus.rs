@@ -0,0 +1,92 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::{self, Game, Seat};
+use crate::gamepad::{self, Button};
+use crate::settings::Settings;
+
+const PLAYER_NAMES: [&str; 2] = ["Player 1 (mouse)", "Player 2 (keys)"];
+
+// Split-screen versus: two Medium boards side by side on the same seed, the left one
+// played with the mouse and the right one with the hole keys. Both rounds run on the
+// same clock, so they end together.
+pub struct Versus {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    boards: [Game; 2],
+}
+
+impl Versus {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let boards = Self::new_boards(&assets, settings, bindings);
+        Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            boards,
+        }
+    }
+
+    fn new_boards(assets: &Arc<Assets>, settings: &Settings, bindings: &Bindings) -> [Game; 2] {
+        let seed = game2::random_seed();
+        [Seat::Mouse, Seat::Keyboard]
+            .map(|seat| Game::with_seed(2, assets.clone(), settings, bindings, seed).with_seat(seat))
+    }
+
+    // Each board keeps the window's aspect ratio in its half, centered vertically
+    fn viewport(side: usize) -> Rect {
+        let (width, height) = (screen_width() / 2.0, screen_height() / 2.0);
+        Rect::new(side as f32 * width, (screen_height() - height) / 2.0, width, height)
+    }
+
+    fn is_over(&self) -> bool {
+        self.boards.iter().all(|board| board.is_over())
+    }
+
+    // Returns true when the players want to go back to the menu
+    pub fn update(&mut self) -> bool {
+        let mut back_to_menu = false;
+        for (side, board) in self.boards.iter_mut().enumerate() {
+            board.set_viewport(Self::viewport(side));
+            back_to_menu |= board.update();
+        }
+
+        // Rematch on a new seed once both rounds are over
+        let rematch = is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || gamepad::is_button_pressed(Button::A);
+        if self.is_over() && rematch {
+            self.boards = Self::new_boards(&self.assets, &self.settings, &self.bindings);
+        }
+        back_to_menu
+    }
+
+    pub fn draw(&self) {
+        for (side, board) in self.boards.iter().enumerate() {
+            board.draw();
+            let viewport = Self::viewport(side);
+            draw_text(PLAYER_NAMES[side], viewport.x + 20.0, viewport.y - 20.0, 40.0, BLACK);
+        }
+        draw_line(screen_width() / 2.0, 0.0, screen_width() / 2.0, screen_height(), 4.0, BLACK);
+
+        if self.is_over() {
+            let scores = [self.boards[0].score(), self.boards[1].score()];
+            let result = if scores[0] == scores[1] {
+                format!("Draw! {} - {}", scores[0], scores[1])
+            } else {
+                let winner = if scores[0] > scores[1] { 0 } else { 1 };
+                format!("{} wins! {} - {}", PLAYER_NAMES[winner], scores[0], scores[1])
+            };
+            let bottom = Self::viewport(0).bottom();
+            let size = measure_text(&result, None, 50, 1.0);
+            draw_text(&result, (screen_width() - size.width) / 2.0, bottom + 60.0, 50.0, DARKGREEN);
+            let hint = format!(
+                "Press Enter or A for a rematch, {} to return to menu.",
+                self.bindings.label(Action::Back),
+            );
+            let size = measure_text(&hint, None, 25, 1.0);
+            draw_text(&hint, (screen_width() - size.width) / 2.0, bottom + 100.0, 25.0, DARKGRAY);
+        }
+    }
+}
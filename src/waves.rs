@@ -0,0 +1,158 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::assets::Assets;
+use crate::bindings::{Action, Bindings};
+use crate::game2::Game;
+use crate::gamepad::{self, Button};
+use crate::settings::Settings;
+
+const INTERMISSION: f64 = 4.0;
+
+struct Wave {
+    name: &'static str,
+    seconds: f64,
+    spawn_rate: f64,
+    mix: (f64, f64), // Chance of a normal mole and of a helmet mole, the rest are cats
+}
+
+const WAVES: [Wave; 6] = [
+    Wave { name: "Warm-up", seconds: 20.0, spawn_rate: 1.0, mix: (1.0, 0.0) },
+    Wave { name: "Helmet rush", seconds: 20.0, spawn_rate: 1.0, mix: (0.4, 0.55) },
+    Wave { name: "Cat walk", seconds: 20.0, spawn_rate: 1.2, mix: (0.55, 0.0) },
+    Wave { name: "Rush hour", seconds: 15.0, spawn_rate: 2.0, mix: (0.7, 0.15) },
+    Wave { name: "Iron moles", seconds: 20.0, spawn_rate: 1.3, mix: (0.2, 0.7) },
+    Wave { name: "Finale", seconds: 25.0, spawn_rate: 1.8, mix: (0.6, 0.2) },
+];
+
+enum Phase {
+    Playing(Box<Game>),
+    Intermission { until: f64 }, // After the wave in `scores.last()`
+    Finished,
+}
+
+// Waves mode: a run of short Medium rounds, each with its own spawn mix and pace, with a
+// breather in between showing how the last wave went. The total over all waves counts.
+pub struct Waves {
+    assets: Arc<Assets>,
+    settings: Settings,
+    bindings: Bindings,
+    phase: Phase,
+    scores: Vec<i32>, // One per finished wave
+}
+
+impl Waves {
+    pub fn new(assets: Arc<Assets>, settings: &Settings, bindings: &Bindings) -> Self {
+        let mut waves = Self {
+            assets,
+            settings: settings.clone(),
+            bindings: bindings.clone(),
+            phase: Phase::Finished,
+            scores: Vec::new(),
+        };
+        waves.start_wave();
+        waves
+    }
+
+    fn start_wave(&mut self) {
+        let wave = &WAVES[self.scores.len()];
+        let mut game = Game::new(2, self.assets.clone(), &self.settings, &self.bindings)
+            .with_duration(wave.seconds)
+            .with_spawn_rate(wave.spawn_rate)
+            .with_mix(wave.mix.0, wave.mix.1);
+        // Waves are short, only the last one ends in a frenzy
+        if self.scores.len() + 1 < WAVES.len() {
+            game = game.with_frenzy(0.0);
+        }
+        self.phase = Phase::Playing(Box::new(game));
+    }
+
+    fn total(&self) -> i32 {
+        self.scores.iter().sum()
+    }
+
+    // Returns true when the player wants to go back to the menu
+    pub fn update(&mut self) -> bool {
+        match &mut self.phase {
+            Phase::Playing(game) => {
+                let back_to_menu = game.update();
+                if game.is_over() {
+                    self.scores.push(game.score());
+                    self.phase = if self.scores.len() == WAVES.len() {
+                        Phase::Finished
+                    } else {
+                        Phase::Intermission { until: get_time() + INTERMISSION }
+                    };
+                }
+                back_to_menu
+            }
+            Phase::Intermission { until } => {
+                let skip = is_key_pressed(KeyCode::Enter)
+                    || is_key_pressed(KeyCode::KpEnter)
+                    || is_mouse_button_pressed(MouseButton::Left)
+                    || gamepad::is_button_pressed(Button::A);
+                if skip || get_time() >= *until {
+                    self.start_wave();
+                }
+                self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back)
+            }
+            Phase::Finished => self.bindings.pressed(Action::Back) || gamepad::is_button_pressed(Button::Back),
+        }
+    }
+
+    pub fn draw(&self) {
+        match &self.phase {
+            Phase::Playing(game) => {
+                game.draw();
+                let number = self.scores.len();
+                let banner = format!("Wave {}/{}: {}   Total: {}", number + 1, WAVES.len(), WAVES[number].name, self.total());
+                let width = measure_text(&banner, None, 32, 1.0).width;
+                draw_text(&banner, screen_width() - width - 20.0, 40.0, 32.0, WHITE);
+            }
+            Phase::Intermission { until } => {
+                let done = self.scores.len();
+                draw_text(&format!("Wave {} cleared!", done), 60.0, 120.0, 60.0, BLACK);
+                draw_text(
+                    &format!("{}: {} points   Total: {}", WAVES[done - 1].name, self.scores[done - 1], self.total()),
+                    60.0,
+                    190.0,
+                    36.0,
+                    DARKGRAY,
+                );
+                draw_text(&format!("Next up, wave {}: {}", done + 1, WAVES[done].name), 60.0, 270.0, 40.0, DARKBLUE);
+                draw_text(
+                    &format!("Starting in {:.0}... (Enter, click or A to go now)", (until - get_time()).max(0.0).ceil()),
+                    60.0,
+                    320.0,
+                    28.0,
+                    DARKGRAY,
+                );
+            }
+            Phase::Finished => {
+                draw_text("All waves done!", 60.0, 120.0, 60.0, BLACK);
+                for (i, (wave, score)) in WAVES.iter().zip(&self.scores).enumerate() {
+                    draw_text(
+                        &format!("Wave {}: {:<12} {:4}", i + 1, wave.name, score),
+                        60.0,
+                        190.0 + i as f32 * 36.0,
+                        32.0,
+                        DARKGRAY,
+                    );
+                }
+                let y = 190.0 + WAVES.len() as f32 * 36.0 + 20.0;
+                draw_text(&format!("Total: {}", self.total()), 60.0, y, 44.0, BLACK);
+                draw_text(
+                    &format!(
+                        "Press {} to play again or {} to return to menu.",
+                        self.bindings.label(Action::Restart),
+                        self.bindings.label(Action::Back),
+                    ),
+                    60.0,
+                    y + 50.0,
+                    26.0,
+                    DARKGRAY,
+                );
+            }
+        }
+    }
+}